@@ -0,0 +1,305 @@
+//helper/bank.rs
+// Where question banks live, how several of them are merged into one pool and how a bank
+// is written without ever leaving a truncated or empty file behind.
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use crate::questions::{load_bank, Question};
+
+pub const BANK_FILE: &str = "questions.json";
+pub const DB_FILE: &str = "questions.db";
+/// Unfinished quiz, see `helper::session`.
+pub const SESSION_FILE: &str = "quiz-session.json";
+/// Bookmarks and flags, see `helper::marks`.
+pub const MARKS_FILE: &str = "bookmarks.json";
+const APP_DIR: &str = "ceh-mocktest";
+/// Number of previous versions kept as `<bank>.bak`, `<bank>.bak.1`, ...
+pub const BACKUPS: usize = 3;
+
+/// Per-user data directory: `$XDG_DATA_HOME/ceh-mocktest`, falling back to
+/// `~/.local/share/ceh-mocktest` (or `%APPDATA%\ceh-mocktest` on Windows).
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir).join(APP_DIR);
+    }
+    if let Some(dir) = env::var_os("APPDATA").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir).join(APP_DIR);
+    }
+    match env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".local").join("share").join(APP_DIR),
+        None => PathBuf::from("."),
+    }
+}
+
+/// The bank used when no `--bank` is given: `questions.json` in the working directory
+/// if there is one (running from the repository), otherwise the one in the data directory.
+pub fn default_bank() -> PathBuf {
+    let local = PathBuf::from(BANK_FILE);
+    if local.exists() {
+        local
+    } else {
+        data_dir().join(BANK_FILE)
+    }
+}
+
+/// Banks given with `--bank`, or the default bank. The first one is the primary bank
+/// that new questions are written to.
+pub fn resolve_banks(given: &[String]) -> Vec<PathBuf> {
+    if given.is_empty() {
+        vec![default_bank()]
+    } else {
+        given.iter().map(PathBuf::from).collect()
+    }
+}
+
+pub fn bank_paths() -> Vec<PathBuf> {
+    super::get_app_config().banks.clone()
+}
+
+pub fn primary_bank() -> PathBuf {
+    bank_paths().into_iter().next().unwrap_or_else(default_bank)
+}
+
+fn next_to_primary_bank(file: &str) -> PathBuf {
+    let primary = primary_bank();
+    match primary.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.join(file),
+        _ => PathBuf::from(file),
+    }
+}
+
+/// The SQLite database lives next to the primary bank.
+pub fn db_path() -> PathBuf {
+    next_to_primary_bank(DB_FILE)
+}
+
+/// The saved unfinished quiz lives next to the primary bank as well.
+pub fn session_path() -> PathBuf {
+    next_to_primary_bank(SESSION_FILE)
+}
+
+pub fn marks_path() -> PathBuf {
+    next_to_primary_bank(MARKS_FILE)
+}
+
+/// Display name of a bank used as provenance.
+pub fn source_name(path: &Path) -> String {
+    path.display().to_string()
+}
+
+/// Merges banks in order. A question id that appears in several banks is taken from the
+/// first bank only; returns the merged pool and the number of questions dropped that way.
+pub fn merge_banks(banks: Vec<Vec<Question>>) -> (Vec<Question>, usize) {
+    let mut merged: Vec<Question> = vec![];
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut shadowed = 0;
+    for bank in banks {
+        for question in bank {
+            if !question.id.is_empty() && seen.contains_key(&question.id) {
+                shadowed += 1;
+                continue;
+            }
+            seen.insert(question.id.clone(), merged.len());
+            merged.push(question);
+        }
+    }
+    (merged, shadowed)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// `<bank>.bak` for the newest backup, `<bank>.bak.N` for older ones.
+pub fn backup_path(path: &Path, generation: usize) -> PathBuf {
+    if generation == 0 {
+        with_suffix(path, ".bak")
+    } else {
+        with_suffix(path, &format!(".bak.{}", generation))
+    }
+}
+
+/// Exclusive lock on `<bank>.lock`; released when dropped. Serializes concurrent imports
+/// and edits, including those of other processes.
+pub struct BankLock {
+    _file: File,
+}
+
+pub fn lock_bank(path: &Path) -> anyhow::Result<BankLock> {
+    create_parent(path)?;
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(with_suffix(path, ".lock"))?;
+    file.lock()?;
+    Ok(BankLock { _file: file })
+}
+
+fn create_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    }
+}
+
+fn rotate_backups(path: &Path) -> std::io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    for generation in (1..BACKUPS).rev() {
+        let older = backup_path(path, generation - 1);
+        if older.exists() {
+            fs::rename(older, backup_path(path, generation))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 0))?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> std::io::Result<()> {
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Replaces `path` with `contents`: written and fsynced to a temporary file first, the old
+/// file rotated into the backups, then renamed over it. A crash leaves either the old or
+/// the new file, never a truncated one.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    replace_file(path, contents, || rotate_backups(path))
+}
+
+/// Like `write_atomic`, without keeping backups; for files that are rewritten all the time.
+pub fn write_replace(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    replace_file(path, contents, || Ok(()))
+}
+
+/// Temporary file, fsync, `before_rename`, rename over `path`, fsync of the directory.
+fn replace_file(path: &Path, contents: &[u8], before_rename: impl FnOnce() -> io::Result<()>) -> anyhow::Result<()> {
+    create_parent(path)?;
+    let temp = with_suffix(path, &format!(".tmp.{}", std::process::id()));
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| before_rename()).and_then(|_| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+    sync_dir(path)?;
+    Ok(())
+}
+
+/// Writes a bank; the caller holds the lock. Replacing a bank that still has questions
+/// with an empty list is refused, that only ever happens after a failed read.
+fn write_bank(path: &Path, questions: &[Question]) -> anyhow::Result<()> {
+    if questions.is_empty() && path.exists() && !load_bank(path).is_ok_and(|existing| existing.is_empty()) {
+        anyhow::bail!("refusing to replace {} with an empty question list", path.display());
+    }
+    write_atomic(path, serde_json::to_string_pretty(questions)?.as_bytes())
+}
+
+/// Writes a whole bank under its lock.
+pub fn save_bank(path: &Path, questions: &[Question]) -> anyhow::Result<()> {
+    let _lock = lock_bank(path)?;
+    write_bank(path, questions)
+}
+
+/// Locked read-modify-write of one bank. `change` returns whether the bank has to be
+/// written. A missing bank starts empty; a bank that cannot be read or parsed is an error
+/// and is never written.
+pub fn modify_bank(path: &Path, change: impl FnOnce(&mut Vec<Question>) -> anyhow::Result<bool>) -> anyhow::Result<bool> {
+    let _lock = lock_bank(path)?;
+    let mut questions = if path.exists() { load_bank(path)? } else { vec![] };
+    let changed = change(&mut questions)?;
+    if changed {
+        write_bank(path, &questions)?;
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Fresh bank path in the system temp directory.
+    fn temp_bank(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ceh-bank-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(BANK_FILE)
+    }
+
+    #[test]
+    fn test_writes_rotate_backups() {
+        let path = temp_bank("backups");
+        for id in ["a", "b", "c", "d", "e"] {
+            modify_bank(&path, |questions| {
+                questions.push(question(id, "x"));
+                Ok(true)
+            }).unwrap();
+        }
+        let count = |p: &Path| load_bank(p).unwrap().len();
+        assert_eq!(count(&path), 5);
+        assert_eq!(count(&backup_path(&path, 0)), 4);
+        assert_eq!(count(&backup_path(&path, BACKUPS - 1)), 2);
+        assert!(!backup_path(&path, BACKUPS).exists());
+    }
+
+    #[test]
+    fn test_unparsable_bank_is_never_overwritten() {
+        let path = temp_bank("corrupt");
+        fs::write(&path, "[{\"question\": ").unwrap();
+        assert!(modify_bank(&path, |_| Ok(true)).is_err());
+        assert!(save_bank(&path, &[]).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[{\"question\": ");
+    }
+
+    #[test]
+    fn test_concurrent_writers_do_not_lose_questions() {
+        let path = temp_bank("concurrent");
+        let writers: Vec<_> = (0..8)
+            .map(|n| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    modify_bank(&path, |questions| {
+                        questions.push(question(&n.to_string(), "x"));
+                        Ok(true)
+                    }).unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(load_bank(&path).unwrap().len(), 8);
+    }
+
+    fn question(id: &str, source: &str) -> Question {
+        Question { id: id.to_string(), source: Some(source.to_string()), ..Question::new() }
+    }
+
+    #[test]
+    fn test_merge_keeps_first_bank_and_provenance() {
+        let official = vec![question("a", "official.json"), question("b", "official.json")];
+        let team = vec![question("b", "team.json"), question("c", "team.json")];
+        let (merged, shadowed) = merge_banks(vec![official, team]);
+        assert_eq!(shadowed, 1);
+        let ids: Vec<(&str, &str)> = merged.iter().map(|q| (q.id.as_str(), q.source.as_deref().unwrap())).collect();
+        assert_eq!(ids, vec![("a", "official.json"), ("b", "official.json"), ("c", "team.json")]);
+    }
+
+    #[test]
+    fn test_resolve_banks_prefers_given_paths() {
+        let banks = resolve_banks(&["team.json".to_string(), "official.json".to_string()]);
+        assert_eq!(banks, vec![PathBuf::from("team.json"), PathBuf::from("official.json")]);
+        assert_eq!(resolve_banks(&[]).len(), 1);
+    }
+}
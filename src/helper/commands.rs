@@ -0,0 +1,301 @@
+//helper/commands.rs
+// One function per subcommand; main only picks the right one from AppConfig.
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use colored::*;
+use crate::questions::{self, check_for_duplicates, load_question_pool};
+use super::{banner, domains, editor, export, history, import, marks, quiz, search, session, stats, validate, AppCommand, AppConfig};
+
+/// Runs the configured subcommand and returns the process exit code.
+pub fn run(config: &AppConfig) -> i32 {
+    let result = match &config.command {
+        AppCommand::Quiz => run_quiz(config),
+        AppCommand::Import { files, .. } if files.is_empty() => import_clipboard(),
+        AppCommand::Import { files, format, dry_run } => import_files(files, *format, *dry_run),
+        AppCommand::Add => add_question(),
+        AppCommand::Edit { id } => edit_question(id),
+        AppCommand::Export { output, format, wrong } => export(output.as_deref(), &config.domains, *format, *wrong),
+        AppCommand::Validate { path } => return validate(path.as_deref(), &config.banks),
+        AppCommand::Stats { sessions } => stats::print_stats(&load_question_pool(), *sessions, config.pass_mark),
+        AppCommand::Tags { apply } => show_tags(*apply),
+        AppCommand::Search { query, limit } => search(config, query, *limit),
+        AppCommand::AiFill { model, save } => ai_fill(model, *save),
+        AppCommand::Flags { resolve } => flags(resolve.as_deref()),
+        AppCommand::DbMigrate => db_migrate(),
+        AppCommand::DbSync { prune } => db_sync(*prune),
+        AppCommand::DbExport { output } => db_export(output.as_deref()),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            1
+        }
+    }
+}
+
+pub fn run_quiz(config: &AppConfig) -> anyhow::Result<()> {
+    banner(config)?;
+    let question_pool = check_for_duplicates(&load_question_pool());
+    if config.resume {
+        let saved = session::load()?.ok_or_else(|| anyhow::anyhow!("There is no unfinished quiz to resume."))?;
+        let shuffle = quiz::QuizOptions::new(config, None).shuffle;
+        quiz::resume_quiz(question_pool, saved, &shuffle)?;
+        return Ok(());
+    }
+    match session::load() {
+        Ok(Some(saved)) => {
+            println!("{}", format!("An unfinished quiz ({} of {} answered) was saved; `quiz --resume` continues it, a new quiz replaces it.",
+                                   saved.answers.len().max(saved.selections.iter().flatten().count()), saved.question_ids.len()).yellow());
+        }
+        Ok(None) => {}
+        // a new quiz replaces the saved one anyway
+        Err(e) => {
+            eprintln!("{}", format!("Removing the saved quiz, it cannot be resumed: {}", e).yellow());
+            session::clear();
+        }
+    }
+    let num_questions = config.count.or_else(quiz::get_num_questions);
+    let options = quiz::QuizOptions::new(config, num_questions);
+    quiz::run_quiz(question_pool, &options);
+    Ok(())
+}
+
+/// Adds the question in the clipboard to the bank, letting Ollama fill it when available.
+pub fn import_clipboard() -> anyhow::Result<()> {
+    let question = questions::do_clipbboard_actions()?;
+    println!("{:#?}", question);
+    Ok(())
+}
+
+/// Imports every question of `files` that is not in the pool yet and prints the report.
+pub fn import_files(files: &[String], format: Option<import::ImportFormat>, dry_run: bool) -> anyhow::Result<()> {
+    let mut entries = vec![];
+    for file in files {
+        entries.extend(import::read_entries(&PathBuf::from(file), format)?);
+    }
+    let report = import::check_entries(entries, &load_question_pool());
+    report.print();
+    let accepted: Vec<questions::Question> = report.accepted.into_iter().map(|a| a.question).collect();
+    if dry_run || accepted.is_empty() {
+        println!("\nNothing stored.");
+        return Ok(());
+    }
+    let source = super::source::configured_source();
+    let count = accepted.len();
+    source.add_all(accepted)?;
+    println!("\nStored {} questions in {}.", count, source.name());
+    Ok(())
+}
+
+/// Authors a new question and stores it in the configured source (the primary bank for JSON).
+pub fn add_question() -> anyhow::Result<()> {
+    let pool = load_question_pool();
+    match editor::author(questions::Question { options: vec![], ..questions::Question::new() }, &pool)? {
+        Some(question) => super::source::configured_source().add(question),
+        None => {
+            println!("Discarded.");
+            Ok(())
+        }
+    }
+}
+
+/// Edits the question with `id` in place, keeping its id so history and bookmarks stay linked.
+pub fn edit_question(id: &str) -> anyhow::Result<()> {
+    let pool = load_question_pool();
+    let question = pool.iter().find(|q| q.id == id).cloned()
+        .ok_or_else(|| anyhow::anyhow!("no question with id {}", id))?;
+    match editor::author(question, &pool)? {
+        Some(question) => super::source::configured_source().update(question),
+        None => {
+            println!("Discarded, nothing changed.");
+            Ok(())
+        }
+    }
+}
+
+/// Writes the questions, filtered by domain/tag and with `wrong` to those answered wrong before,
+/// as JSON, Anki TSV, CSV, Markdown or HTML to a file or stdout.
+pub fn export(output: Option<&str>, wanted: &[String], format: Option<export::ExportFormat>, wrong: bool) -> anyhow::Result<()> {
+    if wrong && !super::is_use_db() {
+        anyhow::bail!("--wrong needs the answer history, rebuild with --features use_db");
+    }
+    // answers are logged by question id, older ones by question text
+    let answered_wrong: HashSet<String> = if wrong {
+        history::load_answer_log()?
+            .into_iter()
+            .filter(|(_, events)| events.iter().any(|e| !e.correct))
+            .map(|(key, _)| key)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    let pool: Vec<_> = load_question_pool()
+        .into_iter()
+        .filter(|q| domains::matches_domains(q, wanted))
+        .filter(|q| !wrong || answered_wrong.contains(&q.id) || answered_wrong.contains(&q.question))
+        .collect();
+    let format = format.unwrap_or_else(|| output.map(|p| export::ExportFormat::from_path(Path::new(p))).unwrap_or(export::ExportFormat::Json));
+    let text = export::render(&pool, format)?;
+    match output {
+        Some(path) => {
+            fs::write(path, text)?;
+            eprintln!("Exported {} questions to {} ({})", pool.len(), path, format);
+        }
+        None => write!(io::stdout(), "{}", text)?,
+    }
+    Ok(())
+}
+
+/// Runs the `validate` subcommand on one file or every configured bank and returns the
+/// process exit code; the worst result of all checked files wins.
+pub fn validate(path: Option<&str>, banks: &[PathBuf]) -> i32 {
+    let paths = match path {
+        Some(path) => vec![PathBuf::from(path)],
+        None => banks.to_vec(),
+    };
+    let mut code = 0;
+    for path in &paths {
+        if paths.len() > 1 {
+            println!("{}", path.display().to_string().bold());
+        }
+        match validate::validate_file(path) {
+            Ok(report) => {
+                report.print();
+                if !report.is_ok() {
+                    code = code.max(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("Error reading {}: {}", path.display(), e);
+                code = 2;
+            }
+        }
+    }
+    code
+}
+
+/// Searches the questions.db FTS5 index with `--source sqlite`, the loaded pool otherwise.
+pub fn search(config: &AppConfig, query: &str, limit: usize) -> anyhow::Result<()> {
+    let terms = search::parse_query(query);
+    if terms.is_empty() {
+        anyhow::bail!("nothing to search for");
+    }
+    let hits = match config.source {
+        #[cfg(feature = "use_db")]
+        super::source::SourceKind::Sqlite => super::repository::QuestionRepository::open()?
+            .search(&search::fts_query(&terms), limit)?
+            .into_iter()
+            .map(|(question, score)| search::SearchHit { question, score })
+            .collect(),
+        _ => {
+            let pool = load_question_pool();
+            search::SearchIndex::new(&pool).search(&terms, limit)
+        }
+    };
+    search::print_hits(&hits, &terms);
+    Ok(())
+}
+
+/// Prints how many questions fall into each CEH domain and optionally stores the suggestions.
+pub fn show_tags(apply: bool) -> anyhow::Result<()> {
+    let pool = load_question_pool();
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for question in &pool {
+        if question.tags.is_empty() {
+            for tag in domains::suggest_tags(question) {
+                counts.entry(tag).or_default().1 += 1;
+            }
+        } else {
+            for tag in &question.tags {
+                counts.entry(tag.clone()).or_default().0 += 1;
+            }
+        }
+    }
+    println!("{:<24} {:>7} {:>10}", "Domain", "tagged", "suggested");
+    for domain in domains::known_domains() {
+        let (tagged, suggested) = counts.remove(domain).unwrap_or_default();
+        println!("{:<24} {:>7} {:>10}", domain, tagged, suggested);
+    }
+    for (tag, (tagged, suggested)) in counts {
+        println!("{:<24} {:>7} {:>10}", tag, tagged, suggested);
+    }
+    let untagged = pool.iter().filter(|q| domains::effective_tags(q).is_empty()).count();
+    println!("{} of {} questions have no tag and no suggestion.", untagged, pool.len());
+    if apply {
+        let tagged = questions::apply_tag_suggestions()?;
+        println!("Stored suggested tags on {} questions.", tagged);
+    }
+    Ok(())
+}
+
+/// Asks Ollama for the answer and a hint of the question in the clipboard.
+pub fn ai_fill(model: &str, save: bool) -> anyhow::Result<()> {
+    let question = questions::get_question_from_clipboard()?;
+    let filled = questions::fill_question_from_ollama(question, model)?;
+    println!("{:#?}", filled);
+    if save {
+        super::source::configured_source().add(filled)?;
+    }
+    Ok(())
+}
+
+/// Lists the flagged questions, or clears the flags of a fixed one.
+pub fn flags(resolve: Option<&str>) -> anyhow::Result<()> {
+    match resolve {
+        Some(id) => match marks::modify_marks(|m| m.resolve_flags(id))? {
+            0 => anyhow::bail!("question {} has no flags", id),
+            n => println!("{}", format!("Removed {} flags of question {}.", n, id).green()),
+        },
+        None => marks::print_flag_report(&marks::load_marks()?, &load_question_pool()),
+    }
+    Ok(())
+}
+
+pub fn db_migrate() -> anyhow::Result<()> {
+    let assigned = questions::migrate_question_ids()?;
+    println!("Assigned ids to {} questions in the question banks.", assigned);
+    #[cfg(feature = "use_db")]{
+        let conn = super::history::open_db()?;
+        let updated = super::history::backfill_question_ids(&conn, &load_question_pool())?;
+        println!("Linked {} recorded answers to question ids.", updated);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "use_db")]
+pub fn db_sync(prune: bool) -> anyhow::Result<()> {
+    let report = super::json_to_sqlite(prune)?;
+    println!(
+        "Synced {}: {} inserted, {} updated, {} unchanged, {} removed.",
+        super::bank::db_path().display(), report.inserted, report.updated, report.unchanged, report.removed
+    );
+    if report.kept > 0 {
+        println!("{} questions are only in the database, use --prune to delete them.", report.kept);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "use_db"))]
+pub fn db_sync(_prune: bool) -> anyhow::Result<()> {
+    anyhow::bail!("this binary was built without SQLite support, rebuild with --features use_db")
+}
+
+#[cfg(feature = "use_db")]
+pub fn db_export(output: Option<&str>) -> anyhow::Result<()> {
+    let path = output.map(PathBuf::from).unwrap_or_else(super::bank::primary_bank);
+    // the database holds every configured bank; a bank only gets its own questions back,
+    // so questions of private team banks never end up in the public one
+    let own_bank = super::bank::bank_paths().contains(&path) || output.is_none();
+    let only_bank = own_bank.then(|| super::bank::source_name(&path));
+    let written = super::sqlite_to_json(&path, only_bank.as_deref())?;
+    println!("Wrote {} questions to {}.", written, path.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "use_db"))]
+pub fn db_export(_output: Option<&str>) -> anyhow::Result<()> {
+    anyhow::bail!("this binary was built without SQLite support, rebuild with --features use_db")
+}
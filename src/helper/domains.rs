@@ -0,0 +1,148 @@
+//helper/domains.rs
+// CEH domains used as question tags, plus keyword rules that suggest tags for untagged questions.
+use std::collections::HashSet;
+use crate::questions::Question;
+
+/// CEH domains and the keywords that hint at them. Keywords are matched as whole words
+/// (or word sequences) in the lower-cased question text and answer.
+pub const DOMAIN_RULES: &[(&str, &[&str])] = &[
+    ("ethics-and-law", &["ethical hacking", "hacker classes", "hacktivist", "script kiddie", "gray hat", "grey hat",
+        "white hat", "black hat", "hipaa", "sox", "pci dss", "gdpr", "dmca", "fisma", "iso 27001", "cyber kill chain",
+        "kill chain", "mitre att", "diamond model", "risk management", "incident management", "non repudiation"]),
+    ("reconnaissance", &["footprinting", "reconnaissance", "osint", "google hacking", "google dork", "search operators",
+        "whois", "shodan", "maltego", "theharvester", "recon ng", "dns interrogation", "zone transfer", "archive org",
+        "wayback machine", "netcraft"]),
+    ("scanning", &["nmap", "port scan", "port scanning", "scanning", "syn scan", "xmas scan", "null scan", "fin scan",
+        "idle scan", "ack scan", "ping sweep", "hping", "hping3", "banner grabbing", "os fingerprinting", "tcp flags"]),
+    ("enumeration", &["enumeration", "snmp", "ldap", "netbios", "smtp enumeration", "ntp", "nbtstat", "rpc", "smb",
+        "null session", "vrfy", "expn"]),
+    ("vulnerability-analysis", &["vulnerability assessment", "vulnerability scanner", "vulnerability scanning", "cvss",
+        "cve", "nessus", "openvas", "qualys", "nikto", "patch management"]),
+    ("system-hacking", &["password cracking", "rainbow table", "privilege escalation", "keylogger", "rootkit",
+        "steganography", "john the ripper", "hashcat", "mimikatz", "pass the hash", "ntlm", "lm hash", "sam file",
+        "dll hijacking", "clearing logs", "alternate data stream", "brute force", "dictionary attack"]),
+    ("malware", &["malware", "trojan", "virus", "worm", "ransomware", "spyware", "adware", "backdoor", "botnet",
+        "dropper", "fileless", "apt", "advanced persistent threat", "sandbox", "sheepdip"]),
+    ("sniffing", &["sniffing", "sniffer", "wireshark", "tcpdump", "arp poisoning", "arp spoofing", "mac flooding",
+        "dhcp starvation", "mac spoofing", "promiscuous mode", "span port", "man in the middle", "mitm"]),
+    ("social-engineering", &["social engineering", "phishing", "spear phishing", "whaling", "vishing", "smishing",
+        "pretexting", "tailgating", "piggybacking", "shoulder surfing", "dumpster diving", "impersonation", "insider"]),
+    ("denial-of-service", &["denial of service", "dos", "ddos", "syn flood", "smurf", "ping of death", "teardrop",
+        "amplification", "slowloris", "flood", "flooding", "botnet"]),
+    ("session-hijacking", &["session hijacking", "session fixation", "session id", "session token", "cookie",
+        "csrf", "cross site request forgery", "tcp sequence", "sequence number"]),
+    ("evasion", &["ids", "ips", "intrusion detection", "intrusion prevention", "firewall", "honeypot", "evasion",
+        "evade", "snort", "fragmentation", "tunneling", "obfuscation", "waf"]),
+    ("web-servers", &["web server", "apache", "iis", "nginx", "directory traversal", "http response splitting",
+        "web cache poisoning", "server misconfiguration", "defacement"]),
+    ("web-applications", &["web application", "xss", "cross site scripting", "owasp", "csrf", "ssrf",
+        "server side request forgery", "xxe", "file inclusion", "command injection", "burp", "parameter tampering",
+        "clickjacking", "api", "webhook", "cookie"]),
+    ("sql-injection", &["sql injection", "sqli", "sql", "sqlmap", "union select", "blind sql", "stored procedure",
+        "database"]),
+    ("wireless", &["wireless", "wi fi", "wifi", "wpa", "wpa2", "wpa3", "wep", "ssid", "access point", "rogue access point",
+        "evil twin", "wardriving", "bluetooth", "bluejacking", "bluesnarfing", "aircrack", "krack", "802 11"]),
+    ("mobile", &["android", "ios", "mobile", "smartphone", "jailbreak", "jailbreaking", "rooting", "sms", "byod",
+        "mdm", "apk"]),
+    ("iot-ot", &["iot", "internet of things", "ot", "scada", "ics", "plc", "modbus", "industrial control",
+        "smart device", "zigbee", "mqtt"]),
+    ("cloud", &["cloud", "aws", "azure", "saas", "paas", "iaas", "container", "docker", "kubernetes", "serverless",
+        "s3 bucket", "cloud service provider", "hypervisor", "virtual machine"]),
+    ("cryptography", &["cryptography", "encryption", "encrypt", "decrypt", "cipher", "aes", "des", "3des", "rsa",
+        "ecc", "diffie hellman", "hash", "md5", "sha", "sha1", "sha256", "pki", "certificate", "digital signature",
+        "ssl", "tls", "pgp", "gpg", "public key", "private key", "symmetric", "asymmetric", "dnssec", "drown", "heartbleed",
+        "poodle", "padding oracle", "birthday attack"]),
+];
+
+/// Names of all known domains, in CEH module order.
+pub fn known_domains() -> Vec<&'static str> {
+    DOMAIN_RULES.iter().map(|(domain, _)| *domain).collect()
+}
+
+/// Lower-cases text and replaces everything that is not alphanumeric by single spaces,
+/// padded so that keywords can be matched as whole words with `contains`.
+fn normalize(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+/// Suggests domains for a question from the keyword rules, in CEH module order.
+pub fn suggest_tags(question: &Question) -> Vec<String> {
+    let text = normalize(&format!("{} {}", question.question, question.correct_answers().join(" ")));
+    DOMAIN_RULES
+        .iter()
+        .filter(|(_, keywords)| keywords.iter().any(|k| text.contains(&format!(" {} ", normalize(k).trim()))))
+        .map(|(domain, _)| domain.to_string())
+        .collect()
+}
+
+/// Stored tags of a question, or the suggested ones if it has none yet.
+pub fn effective_tags(question: &Question) -> Vec<String> {
+    if question.tags.is_empty() {
+        suggest_tags(question)
+    } else {
+        question.tags.clone()
+    }
+}
+
+/// True if the question carries at least one of the wanted tags (case-insensitive).
+/// An empty filter matches every question.
+pub fn matches_domains(question: &Question, wanted: &[String]) -> bool {
+    if wanted.is_empty() {
+        return true;
+    }
+    let wanted: HashSet<String> = wanted.iter().map(|d| d.to_lowercase()).collect();
+    effective_tags(question).iter().any(|tag| wanted.contains(&tag.to_lowercase()))
+}
+
+/// Stores the suggested tags on every question that has none. Returns how many got tags.
+pub fn apply_suggestions(questions: &mut [Question]) -> usize {
+    let mut tagged = 0;
+    for question in questions.iter_mut().filter(|q| q.tags.is_empty()) {
+        question.tags = suggest_tags(question);
+        if !question.tags.is_empty() {
+            tagged += 1;
+        }
+    }
+    tagged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(text: &str, answer: &str) -> Question {
+        Question {
+            question: text.to_string(),
+            answer: answer.to_string(),
+            options: vec![answer.to_string()],
+            ..Question::new()
+        }
+    }
+
+    #[test]
+    fn test_suggest_tags_matches_whole_words() {
+        let dnssec = question("Which extension to DNS provides origin authentication?", "DNSSEC");
+        assert_eq!(suggest_tags(&dnssec), vec!["cryptography"]);
+
+        let scan = question("Which Nmap option performs an XMAS scan?", "-sX");
+        assert_eq!(suggest_tags(&scan), vec!["scanning"]);
+
+        // "dos" must not match inside "windows"
+        let windows = question("Which Windows file stores local password hashes?", "SAM file");
+        assert!(!suggest_tags(&windows).contains(&"denial-of-service".to_string()));
+    }
+
+    #[test]
+    fn test_stored_tags_win_over_suggestions() {
+        let mut q = question("Which tool cracks WPA2 handshakes?", "aircrack-ng");
+        assert!(matches_domains(&q, &["Wireless".to_string()]));
+        q.tags = vec!["system-hacking".to_string()];
+        assert!(!matches_domains(&q, &["wireless".to_string()]));
+        assert!(matches_domains(&q, &[]));
+    }
+}
@@ -0,0 +1,310 @@
+//helper/editor.rs
+// Interactive authoring of questions for the `add` and `edit` subcommands.
+// Every prompt shows the current value in brackets: Enter keeps it, `-` clears it.
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use colored::*;
+use crate::questions::{self, content_id, parse_selection, Difficulty, Question};
+use super::{domains, validate};
+use super::validate::{Severity, MAX_OPTIONS, MIN_OPTIONS};
+
+/// Reads answers for the prompts from any input, so the editor can be driven by tests.
+pub struct Prompter<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Prompter { input, output }
+    }
+
+    /// Asks for one line; `None` when the input is closed.
+    fn read(&mut self, prompt: &str) -> anyhow::Result<Option<String>> {
+        write!(self.output, "{}", prompt)?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim().to_string()))
+    }
+
+    fn read_required(&mut self, prompt: &str) -> anyhow::Result<String> {
+        self.read(prompt)?.ok_or_else(|| anyhow::anyhow!("input closed, nothing saved"))
+    }
+
+    /// Asks for a text; Enter keeps `current`, `-` clears it.
+    pub fn text(&mut self, label: &str, current: Option<&str>) -> anyhow::Result<Option<String>> {
+        let prompt = match current {
+            Some(value) => format!("{} [{}]: ", label, value),
+            None => format!("{}: ", label),
+        };
+        let line = self.read_required(&prompt)?;
+        Ok(match line.as_str() {
+            "" => current.map(str::to_string),
+            "-" => None,
+            _ => Some(line),
+        })
+    }
+
+    /// Walks through the existing options (Enter keeps, `-` removes, text replaces) and then
+    /// takes new ones until an empty line, at least `MIN_OPTIONS` and at most `MAX_OPTIONS`.
+    pub fn options(&mut self, current: &[String]) -> anyhow::Result<Vec<String>> {
+        writeln!(self.output, "Options (Enter keeps, - removes, empty line after the last one finishes):")?;
+        let mut options = vec![];
+        for option in current {
+            match self.read_required(&format!("  {}. [{}]: ", options.len() + 1, option))?.as_str() {
+                "" => options.push(option.clone()),
+                "-" => {}
+                replaced => options.push(replaced.to_string()),
+            }
+        }
+        while options.len() < MAX_OPTIONS {
+            let line = self.read_required(&format!("  {}. ", options.len() + 1))?;
+            if line.is_empty() {
+                if options.len() >= MIN_OPTIONS {
+                    break;
+                }
+                writeln!(self.output, "{}", format!("A question needs at least {} options.", MIN_OPTIONS).red())?;
+                continue;
+            }
+            options.push(line);
+        }
+        Ok(options)
+    }
+
+    /// Option indexes of the correct answer(s), e.g. `2` or `1,3` for select-all questions.
+    pub fn correct(&mut self, options: &[String], current: &[usize]) -> anyhow::Result<Vec<usize>> {
+        if options.is_empty() {
+            anyhow::bail!("the question has no options to choose the correct one from");
+        }
+        let shown = current.iter().map(|i| (i + 1).to_string()).collect::<Vec<_>>().join(",");
+        loop {
+            let line = self.read_required(&format!("Correct option number(s), e.g. 2 or 1,3 [{}]: ", shown))?;
+            if line.is_empty() && !current.is_empty() {
+                return Ok(current.to_vec());
+            }
+            match parse_selection(&line, options.len(), true) {
+                Some(selection) => return Ok(selection),
+                None => writeln!(self.output, "{}", format!("Please enter numbers from 1 to {}.", options.len()).red())?,
+            }
+        }
+    }
+
+    /// Comma separated tags; without current tags the keyword suggestions are offered.
+    pub fn tags(&mut self, current: &[String], suggested: &[String]) -> anyhow::Result<Vec<String>> {
+        let default = if current.is_empty() { suggested } else { current };
+        let line = self.read_required(&format!("Tags, comma separated [{}]: ", default.join(", ")))?;
+        Ok(match line.as_str() {
+            "" => default.to_vec(),
+            "-" => vec![],
+            _ => line.split(',').map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect(),
+        })
+    }
+
+    /// Why each wrong option is wrong; notes of removed or now correct options are dropped.
+    pub fn distractors(&mut self, question: &Question) -> anyhow::Result<BTreeMap<String, String>> {
+        let correct = question.correct_answers();
+        let mut notes = BTreeMap::new();
+        for (i, option) in question.options.iter().enumerate().filter(|(_, o)| !correct.contains(&o.as_str())) {
+            let label = format!("Why {}. {} is wrong", i + 1, option);
+            if let Some(note) = self.text(&label, question.distractors.get(option).map(String::as_str))? {
+                notes.insert(option.clone(), note);
+            }
+        }
+        Ok(notes)
+    }
+
+    /// References separated by `;`, module titles may contain commas.
+    pub fn references(&mut self, current: &[String]) -> anyhow::Result<Vec<String>> {
+        let joined = current.join("; ");
+        let current = Some(joined.as_str()).filter(|j| !j.is_empty());
+        Ok(match self.text("References, ; separated (e.g. Module 03: Scanning Networks)", current)? {
+            Some(line) => line.split(';').map(|r| r.trim().to_string()).filter(|r| !r.is_empty()).collect(),
+            None => vec![],
+        })
+    }
+
+    pub fn difficulty(&mut self, current: Option<Difficulty>) -> anyhow::Result<Option<Difficulty>> {
+        loop {
+            let current_label = current.map(|d| format!("{:?}", d).to_lowercase());
+            match self.text("Difficulty (easy, medium, hard)", current_label.as_deref())? {
+                None => return Ok(None),
+                Some(label) => match label.to_lowercase().as_str() {
+                    "easy" => return Ok(Some(Difficulty::Easy)),
+                    "medium" => return Ok(Some(Difficulty::Medium)),
+                    "hard" => return Ok(Some(Difficulty::Hard)),
+                    _ => writeln!(self.output, "{}", "Please enter easy, medium or hard.".red())?,
+                },
+            }
+        }
+    }
+
+    pub fn confirm(&mut self, prompt: &str) -> anyhow::Result<char> {
+        let line = self.read_required(prompt)?;
+        Ok(line.chars().next().map(|c| c.to_ascii_lowercase()).unwrap_or(' '))
+    }
+}
+
+/// Prompts for every field of `question`, starting from its current values.
+pub fn edit_fields<R: BufRead, W: Write>(prompter: &mut Prompter<R, W>, question: &Question) -> anyhow::Result<Question> {
+    let mut edited = question.clone();
+    loop {
+        match prompter.text("Question", Some(&edited.question).filter(|q| !q.is_empty()).map(String::as_str))? {
+            Some(text) => {
+                edited.question = text;
+                break;
+            }
+            None => writeln!(prompter.output, "{}", "The question text is required.".red())?,
+        }
+    }
+    let current_options: Vec<String> = edited.options.iter().filter(|o| !o.is_empty()).cloned().collect();
+    edited.options = prompter.options(&current_options)?;
+    let current = edited.correct_indexes();
+    let correct = prompter.correct(&edited.options, &current)?;
+    let answers: Vec<String> = correct.iter().map(|&i| edited.options[i].clone()).collect();
+    if answers.len() > 1 {
+        edited.answer = answers.join("; ");
+        edited.answers = answers;
+    } else {
+        edited.answer = answers.into_iter().next().unwrap_or_default();
+        edited.answers = vec![];
+    }
+    edited.hint = prompter.text("Hint", edited.hint.as_deref())?;
+    edited.explanation = prompter.text("Explanation", edited.explanation.as_deref())?;
+    edited.distractors = prompter.distractors(&edited)?;
+    edited.references = prompter.references(&edited.references)?;
+    let suggested = domains::suggest_tags(&edited);
+    edited.tags = prompter.tags(&edited.tags, &suggested)?;
+    edited.difficulty = prompter.difficulty(edited.difficulty)?;
+    if edited.id.is_empty() {
+        edited.id = content_id(&edited);
+    }
+    Ok(edited)
+}
+
+/// Errors and warnings for the edited question, including duplicates of other questions.
+pub fn check(question: &Question, pool: &[Question]) -> Vec<(Severity, String)> {
+    let mut issues: Vec<(Severity, String)> = validate::validate_questions(std::slice::from_ref(question))
+        .issues
+        .into_iter()
+        .map(|i| (i.severity, i.message))
+        .collect();
+    if let Some(other) = pool.iter().filter(|q| q.id != question.id).find(|q| q.question.trim() == question.question.trim()) {
+        issues.push((Severity::Error, format!("same question text as {}", other.id)));
+    }
+    issues
+}
+
+/// Shows the question the way `ask_question` does, followed by the answer details.
+fn preview(question: &Question) {
+    questions::show_question("Preview", question, &question.correct_indexes());
+    println!("\n{} {}", "Correct:".green().bold(), question.correct_answers().join("; "));
+    if let Some(hint) = &question.hint {
+        println!("{} {}", "Hint:".bold(), hint);
+    }
+    if let Some(explanation) = &question.explanation {
+        println!("{} {}", "Explanation:".bold(), explanation);
+    }
+    for (option, why) in &question.distractors {
+        println!("{} {}: {}", "Wrong:".bold(), option, why);
+    }
+    if !question.references.is_empty() {
+        println!("{} {}", "References:".bold(), question.references.join("; "));
+    }
+    if !question.tags.is_empty() {
+        println!("{} {}", "Tags:".bold(), question.tags.join(", "));
+    }
+    if let Some(difficulty) = question.difficulty {
+        println!("{} {:?}", "Difficulty:".bold(), difficulty);
+    }
+}
+
+/// Runs the editor on stdin until the question is saved or discarded.
+/// Returns the question to store, or `None` when the author aborts.
+pub fn author(question: Question, pool: &[Question]) -> anyhow::Result<Option<Question>> {
+    let stdin = io::stdin();
+    let mut prompter = Prompter::new(stdin.lock(), io::stdout());
+    let mut question = question;
+    loop {
+        question = edit_fields(&mut prompter, &question)?;
+        preview(&question);
+        let issues = check(&question, pool);
+        for (severity, message) in &issues {
+            match severity {
+                Severity::Error => println!("{} {}", "error".red().bold(), message),
+                Severity::Warning => println!("{} {}", "warning".yellow().bold(), message),
+            }
+        }
+        let has_errors = issues.iter().any(|(severity, _)| *severity == Severity::Error);
+        let prompt = if has_errors { "\nFix the errors: (e)dit again or (a)bort? " } else { "\n(s)ave, (e)dit again or (a)bort? " };
+        match prompter.confirm(prompt)? {
+            's' if !has_errors => return Ok(Some(question)),
+            'a' => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn run(input: &str, question: &Question) -> Question {
+        let mut prompter = Prompter::new(Cursor::new(input.to_string()), Vec::new());
+        edit_fields(&mut prompter, question).unwrap()
+    }
+
+    #[test]
+    fn test_new_question_from_prompts() {
+        let input = "Which port does SSH use?\n21\n22\n23\n\n2\nThink secure shell\n-\nFTP\n\nModule 03: Scanning Networks; RFC 4253\nscanning, Enumeration\nEASY\n";
+        let question = run(input, &Question::new());
+        assert_eq!(question.options, vec!["21", "22", "23"]);
+        assert_eq!(question.answer, "22");
+        assert!(question.answers.is_empty());
+        assert_eq!(question.hint.as_deref(), Some("Think secure shell"));
+        assert_eq!(question.explanation, None);
+        assert_eq!(question.distractors, BTreeMap::from([("21".to_string(), "FTP".to_string())]));
+        assert_eq!(question.references, vec!["Module 03: Scanning Networks", "RFC 4253"]);
+        assert_eq!(question.tags, vec!["scanning", "enumeration"]);
+        assert_eq!(question.difficulty, Some(Difficulty::Easy));
+        assert_eq!(question.id, content_id(&question));
+        assert!(check(&question, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_edit_keeps_values_and_switches_to_select_all() {
+        let original = run("Pick the hashes\nMD5\nAES\nSHA-1\n\n1\n\n\n\n\n\n\n\n", &Question::new());
+        // keep text, drop AES, keep the rest, add SHA-256, then choose 1 and 3
+        let edited = run("\n\n-\n\nSHA-256\n\n1,3\n\nOne-way functions\n\n\n-\nhard\n", &original);
+        assert_eq!(edited.id, original.id);
+        assert_eq!(edited.options, vec!["MD5", "SHA-1", "SHA-256"]);
+        assert_eq!(edited.answers, vec!["MD5", "SHA-256"]);
+        assert_eq!(edited.explanation.as_deref(), Some("One-way functions"));
+        assert!(edited.tags.is_empty());
+        assert_eq!(edited.difficulty, Some(Difficulty::Hard));
+    }
+
+    #[test]
+    fn test_options_are_required_and_limited() {
+        let mut prompter = Prompter::new(Cursor::new("\n21\n\n22\n\n".to_string()), Vec::new());
+        assert_eq!(prompter.options(&[]).unwrap(), vec!["21", "22"]);
+        assert!(String::from_utf8(prompter.output).unwrap().contains("at least 2 options"));
+
+        let input: String = (1..=12).map(|i| format!("{}\n", i)).collect();
+        let mut prompter = Prompter::new(Cursor::new(input), Vec::new());
+        assert_eq!(prompter.options(&[]).unwrap().len(), MAX_OPTIONS);
+        assert!(prompter.correct(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_check_reports_duplicate_text() {
+        let question = run("Which port does SSH use?\n21\n22\n\n2\n\n\n\n\n-\n\n", &Question::new());
+        let mut other = question.clone();
+        other.id = "other".to_string();
+        let issues = check(&question, &[other]);
+        assert_eq!(issues, vec![(Severity::Error, "same question text as other".to_string())]);
+    }
+}
@@ -0,0 +1,269 @@
+//helper/exam.rs
+// Exam simulation: fixed question count, countdown, no feedback until the end,
+// questions can be flagged and revisited before the exam is submitted.
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use colored::*;
+use crate::questions::{self, parse_selection, Answer, Outcome, TypedQuestion};
+use super::quiz::{format_duration, QuizReport};
+use super::session::{checkpoint, SavedSession};
+use super::shuffle::{Presentation, Shuffle};
+
+/// The real CEH exam gives four hours for 125 questions.
+pub const EXAM_TIME_LIMIT: Duration = Duration::from_secs(4 * 60 * 60);
+
+/// Answers and flags collected while the exam is running.
+#[derive(Debug, Clone)]
+pub struct ExamSession {
+    pub questions: Vec<TypedQuestion>,
+    /// The questions as shown; selections are display indexes into these.
+    pub presentations: Vec<Presentation>,
+    pub selections: Vec<Option<Vec<usize>>>,
+    pub flagged: Vec<bool>,
+    pub time_spent: Vec<Duration>,
+    pub current: usize,
+}
+
+impl ExamSession {
+    pub fn new(questions: Vec<TypedQuestion>, shuffle: &Shuffle) -> Self {
+        let presentations = questions.iter().map(|q| Presentation::new(&q.question, shuffle)).collect();
+        Self::with_presentations(questions, presentations)
+    }
+
+    pub fn with_presentations(questions: Vec<TypedQuestion>, presentations: Vec<Presentation>) -> Self {
+        let count = questions.len();
+        ExamSession {
+            presentations,
+            questions,
+            selections: vec![None; count],
+            flagged: vec![false; count],
+            time_spent: vec![Duration::ZERO; count],
+            current: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    pub fn answered(&self) -> usize {
+        self.selections.iter().filter(|s| s.is_some()).count()
+    }
+
+    pub fn flagged_count(&self) -> usize {
+        self.flagged.iter().filter(|f| **f).count()
+    }
+
+    pub fn select(&mut self, selection: Vec<usize>) {
+        self.selections[self.current] = Some(selection);
+    }
+
+    pub fn toggle_flag(&mut self) {
+        self.flagged[self.current] = !self.flagged[self.current];
+    }
+
+    pub fn go_to(&mut self, index: usize) -> bool {
+        if index < self.len() {
+            self.current = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.current = (self.current + 1).min(self.len().saturating_sub(1));
+    }
+
+    pub fn previous(&mut self) {
+        self.current = self.current.saturating_sub(1);
+    }
+
+    /// Grades all answers; unanswered questions count as skipped.
+    pub fn grade(&self, pass_mark: f64, elapsed: Duration) -> QuizReport {
+        let mut report = QuizReport::new(pass_mark);
+        for (index, typed_question) in self.questions.iter().enumerate() {
+            let question = &typed_question.question;
+            let presentation = &self.presentations[index];
+            let answer = match &self.selections[index] {
+                Some(selection) => Answer {
+                    outcome: if presentation.is_correct(selection) { Outcome::Correct } else { Outcome::Wrong },
+                    chosen: Some(presentation.chosen(selection)),
+                    hint_used: false,
+                },
+                None => Answer { outcome: Outcome::Skipped, chosen: None, hint_used: false },
+            };
+            report.record(index + 1, question, answer, self.time_spent[index]);
+        }
+        report.elapsed = elapsed;
+        report
+    }
+
+    fn print_overview(&self) {
+        println!("\n{}", "Exam overview".bold());
+        for index in 0..self.len() {
+            let status = match (&self.selections[index], self.flagged[index]) {
+                (_, true) => "flagged".yellow(),
+                (Some(_), false) => "answered".green(),
+                (None, false) => "open".red(),
+            };
+            print!("{:>4}: {:<9}", index + 1, status);
+            if (index + 1) % 6 == 0 {
+                println!();
+            }
+        }
+        println!("\n{} answered, {} open, {} flagged", self.answered(), self.len() - self.answered(), self.flagged_count());
+    }
+}
+
+fn read_line(prompt: &str) -> String {
+    print!("{}", prompt);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_uppercase()
+}
+
+/// Runs the exam interactively and returns the graded report once it is submitted or time is up.
+/// Answers, flags and the clock are taken over from `saved` and written back to it after every
+/// input; `None` when the user exits with X.
+pub fn run_exam(session: &mut ExamSession, mut saved: SavedSession, pass_mark: f64, time_limit: Duration) -> Option<QuizReport> {
+    saved.restore_exam(session);
+    let earlier = saved.elapsed();
+    let start = Instant::now();
+    let elapsed = || earlier + start.elapsed();
+
+    while !session.is_empty() {
+        let remaining = time_limit.saturating_sub(elapsed());
+        if remaining.is_zero() {
+            println!("{}", "Time is up! Your exam is submitted.".red().bold());
+            break;
+        }
+
+        let index = session.current;
+        let flag = if session.flagged[index] { " [flagged]".yellow().to_string() } else { String::new() };
+        let title = format!("Question {}/{}{}   Time left: {}", index + 1, session.len(), flag, format_duration(remaining));
+        let selected = session.selections[index].clone().unwrap_or_default();
+        questions::show_question(&title, &session.presentations[index].question, &selected);
+
+        let shown_at = Instant::now();
+        let input = read_line("\nAnswer [numbers], F flag, N next, P previous, G<n> go to, R overview, E end exam, X exit: ");
+        session.time_spent[index] += shown_at.elapsed();
+        // the prompt blocks, the time may have run out while it was open
+        if elapsed() >= time_limit {
+            println!("{}", "Time is up! The last input came too late, your exam is submitted.".red().bold());
+            break;
+        }
+
+        match input.as_str() {
+            "X" => {
+                saved.capture_exam(session, elapsed());
+                checkpoint(&mut saved);
+                println!("{}", "Exam saved, continue it with `quiz --resume`.".yellow());
+                return None;
+            }
+            "F" => session.toggle_flag(),
+            "N" | "" => session.next(),
+            "P" => session.previous(),
+            "R" => {
+                session.print_overview();
+                read_line("Press Enter to continue...");
+            }
+            "E" => {
+                let open = session.len() - session.answered();
+                let confirm = read_line(&format!("{} questions are unanswered and {} flagged. Submit the exam? (yes/no): ", open, session.flagged_count()));
+                if confirm == "YES" || confirm == "Y" {
+                    break;
+                }
+            }
+            goto if goto.starts_with('G') => {
+                let target = goto[1..].trim().parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+                if !target.is_some_and(|t| session.go_to(t)) {
+                    read_line(&format!("{}", "No such question. Press Enter to continue...".red()));
+                }
+            }
+            answer => {
+                let question = &session.presentations[index].question;
+                match parse_selection(answer, question.options.len(), question.is_multi_answer()) {
+                    Some(selection) => {
+                        session.select(selection);
+                        session.next();
+                    }
+                    None => {
+                        read_line(&format!("{}", "Invalid input. Press Enter to continue...".red()));
+                    }
+                }
+            }
+        }
+        saved.capture_exam(session, elapsed());
+        checkpoint(&mut saved);
+    }
+
+    let report = session.grade(pass_mark, elapsed());
+    print_exam_review(session, &report);
+    Some(report)
+}
+
+/// Lists every question that was not answered correctly together with the right answer
+/// and its explanation.
+fn print_exam_review(session: &ExamSession, report: &QuizReport) {
+    let missed: Vec<_> = report.results.iter().filter(|r| r.outcome != Outcome::Correct).collect();
+    if missed.is_empty() {
+        return;
+    }
+    println!("\n{}", "Exam review".bold());
+    for result in missed {
+        let flagged = if session.flagged[result.number - 1] { " [flagged]".yellow().to_string() } else { String::new() };
+        println!("\n{}. {}{}", result.number, result.question, flagged);
+        match &result.chosen {
+            Some(chosen) => println!("   Your answer:    {}", chosen.red()),
+            None => println!("   Your answer:    {}", "(none)".red()),
+        }
+        println!("   Correct answer: {}", result.correct_answer.green());
+        let chosen = session.selections[result.number - 1].as_deref().unwrap_or_default();
+        for line in questions::explanation_lines(&session.presentations[result.number - 1].question, chosen) {
+            println!("   {}", line.dimmed());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::{Question, QuestionType};
+
+    fn typed(answer: &str) -> TypedQuestion {
+        let question = Question {
+            answer: answer.to_string(),
+            options: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+            ..Question::new()
+        };
+        TypedQuestion { qtype: QuestionType::DefaultItem, question }
+    }
+
+    #[test]
+    fn test_exam_session_navigation_and_grading() {
+        let mut session = ExamSession::new(vec![typed("A"), typed("B"), typed("C")], &Shuffle::off());
+        session.select(vec![0]);
+        session.next();
+        session.toggle_flag();
+        session.select(vec![2]);
+        session.next();
+        session.next();
+        assert_eq!(session.current, 2);
+        assert!(!session.go_to(3));
+        assert!(session.go_to(0));
+        session.previous();
+        assert_eq!(session.current, 0);
+        assert_eq!((session.answered(), session.flagged_count()), (2, 1));
+
+        let report = session.grade(50.0, Duration::from_secs(60));
+        assert_eq!((report.correct(), report.wrong(), report.skipped()), (1, 1, 1));
+        assert_eq!(report.results[1].chosen.as_deref(), Some("C"));
+        assert!(!report.passed());
+    }
+}
@@ -0,0 +1,268 @@
+//helper/export.rs
+// Serializers for the `export` subcommand: the bank JSON, Anki-importable TSV, CSV and
+// Markdown (both readable by `import` again) and a self-contained printable HTML sheet
+// with an answer key.
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use crate::questions::Question;
+use super::domains;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Anki,
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// By the extension of the output file; JSON when it says nothing.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("tsv") | Some("txt") => ExportFormat::Anki,
+            Some("csv") => ExportFormat::Csv,
+            Some("md") | Some("markdown") => ExportFormat::Markdown,
+            Some("html") | Some("htm") => ExportFormat::Html,
+            _ => ExportFormat::Json,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ExportFormat::Json),
+            "anki" | "tsv" => Ok(ExportFormat::Anki),
+            "csv" => Ok(ExportFormat::Csv),
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            other => Err(format!("unknown export format '{}', expected json, anki, csv, markdown or html", other)),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Json => write!(f, "json"),
+            ExportFormat::Anki => write!(f, "anki"),
+            ExportFormat::Csv => write!(f, "csv"),
+            ExportFormat::Markdown => write!(f, "markdown"),
+            ExportFormat::Html => write!(f, "html"),
+        }
+    }
+}
+
+pub fn render(questions: &[Question], format: ExportFormat) -> anyhow::Result<String> {
+    Ok(match format {
+        ExportFormat::Json => serde_json::to_string_pretty(questions)? + "\n",
+        ExportFormat::Anki => to_anki(questions),
+        ExportFormat::Csv => to_csv(questions),
+        ExportFormat::Markdown => to_markdown(questions),
+        ExportFormat::Html => to_html(questions),
+    })
+}
+
+fn letter(index: usize) -> char {
+    (b'A' + index as u8) as char
+}
+
+/// "B" or "A, C" for select-all questions.
+fn answer_letters(question: &Question) -> String {
+    question.correct_indexes().iter().map(|&i| letter(i).to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// "B. 23: Telnet" for every option with a distractor note.
+fn distractor_notes(question: &Question) -> Vec<String> {
+    question
+        .options
+        .iter()
+        .enumerate()
+        .filter_map(|(i, o)| question.distractors.get(o).map(|why| format!("{}. {}: {}", letter(i), o, why)))
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Anki "Notes in Plain Text": front, back and tags separated by tabs, HTML enabled.
+pub fn to_anki(questions: &[Question]) -> String {
+    // fields may not contain tabs or line breaks
+    let field = |text: &str| escape_html(text).replace('\t', " ").replace('\n', "<br>");
+    let mut out = String::from("#separator:tab\n#html:true\n#tags column:3\n");
+    for question in questions {
+        let options: Vec<String> = question.options.iter().enumerate().map(|(i, o)| format!("{}. {}", letter(i), field(o))).collect();
+        let front = format!("{}<br><br>{}", field(&question.question), options.join("<br>"));
+        let mut back = format!("<b>{}</b>: {}", answer_letters(question), field(&question.correct_answers().join("; ")));
+        if let Some(explanation) = &question.explanation {
+            back.push_str(&format!("<br><br>{}", field(explanation)));
+        }
+        for note in distractor_notes(question) {
+            back.push_str(&format!("<br>{}", field(&note)));
+        }
+        if !question.references.is_empty() {
+            back.push_str(&format!("<br><br>{}", field(&question.references.join("; "))));
+        }
+        if let Some(hint) = &question.hint {
+            back.push_str(&format!("<br><br><i>Hint: {}</i>", field(hint)));
+        }
+        // Anki separates tags by spaces; the suggested domains of untagged questions, as `--tag` filters by them
+        let tags: Vec<String> = domains::effective_tags(question).iter().map(|t| t.replace(char::is_whitespace, "_")).collect();
+        out.push_str(&format!("{}\t{}\t{}\n", front, back, tags.join(" ")));
+    }
+    out
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// One row per question with a column per option letter; the header matches what
+/// `import` expects.
+pub fn to_csv(questions: &[Question]) -> String {
+    let width = questions.iter().map(|q| q.options.len()).max().unwrap_or(0).max(4);
+    let mut header = vec!["id".to_string(), "question".to_string()];
+    header.extend((0..width).map(|i| letter(i).to_ascii_lowercase().to_string()));
+    header.extend(["answer", "hint", "explanation", "tags"].map(String::from));
+    let mut out = header.join(",") + "\n";
+    for question in questions {
+        let mut row = vec![csv_field(&question.id), csv_field(&question.question)];
+        row.extend((0..width).map(|i| question.options.get(i).map(|o| csv_field(o)).unwrap_or_default()));
+        row.push(csv_field(&answer_letters(question)));
+        row.push(csv_field(question.hint.as_deref().unwrap_or("")));
+        row.push(csv_field(question.explanation.as_deref().unwrap_or("")));
+        row.push(csv_field(&domains::effective_tags(question).join(";")));
+        out.push_str(&(row.join(",") + "\n"));
+    }
+    out
+}
+
+/// A heading per question with the options as a task list, `- [x]` on the correct ones.
+pub fn to_markdown(questions: &[Question]) -> String {
+    let mut out = String::from("# CEH mock test questions\n");
+    for (number, question) in questions.iter().enumerate() {
+        out.push_str(&format!("\n## Question {}\n\n{}\n\n", number + 1, question.question));
+        let correct = question.correct_indexes();
+        for (index, option) in question.options.iter().enumerate() {
+            out.push_str(&format!("- [{}] {}\n", if correct.contains(&index) { "x" } else { " " }, option));
+        }
+        if let Some(explanation) = &question.explanation {
+            out.push_str(&format!("\n**Explanation:** {}\n", explanation));
+        }
+        if let Some(hint) = &question.hint {
+            out.push_str(&format!("\n**Hint:** {}\n", hint));
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;max-width:50em;margin:2em auto;line-height:1.4}\
+ol.options{list-style-type:upper-alpha}li.question{margin-bottom:1.2em}\
+.key{page-break-before:always}.key li{margin-bottom:.6em}.explanation{color:#444}\
+@media print{body{margin:0}}";
+
+/// Printable sheet: the numbered questions, then the answer key on a new page.
+pub fn to_html(questions: &[Question]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>CEH mock test</title>\n<style>{}</style>\n</head>\n<body>\n<h1>CEH mock test</h1>\n<ol>\n",
+        HTML_STYLE
+    );
+    for question in questions {
+        out.push_str(&format!("<li class=\"question\">{}\n<ol class=\"options\">\n", escape_html(&question.question)));
+        for option in &question.options {
+            out.push_str(&format!("<li>{}</li>\n", escape_html(option)));
+        }
+        out.push_str("</ol>\n</li>\n");
+    }
+    out.push_str("</ol>\n<section class=\"key\">\n<h2>Answer key</h2>\n<ol>\n");
+    for question in questions {
+        out.push_str(&format!("<li><b>{}</b> {}", answer_letters(question), escape_html(&question.correct_answers().join("; "))));
+        if let Some(explanation) = &question.explanation {
+            out.push_str(&format!("<div class=\"explanation\">{}</div>", escape_html(explanation)));
+        }
+        for note in distractor_notes(question) {
+            out.push_str(&format!("<div class=\"explanation\">{}</div>", escape_html(&note)));
+        }
+        if !question.references.is_empty() {
+            out.push_str(&format!("<div class=\"explanation\"><i>{}</i></div>", escape_html(&question.references.join("; "))));
+        }
+        out.push_str("</li>\n");
+    }
+    out.push_str("</ol>\n</section>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::import;
+
+    fn questions() -> Vec<Question> {
+        vec![
+            Question {
+                id: "ssh".to_string(),
+                question: "Which port does \"SSH\" use?".to_string(),
+                options: vec!["21".to_string(), "22".to_string(), "23, maybe".to_string()],
+                answer: "22".to_string(),
+                hint: Some("Secure shell".to_string()),
+                tags: vec!["scanning".to_string(), "system hacking".to_string()],
+                ..Question::new()
+            },
+            Question {
+                id: "hash".to_string(),
+                question: "Which are <hashes>?".to_string(),
+                options: vec!["MD5".to_string(), "AES".to_string(), "SHA-1".to_string()],
+                answer: "MD5; SHA-1".to_string(),
+                answers: vec!["MD5".to_string(), "SHA-1".to_string()],
+                explanation: Some("AES is a cipher".to_string()),
+                ..Question::new()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_anki_fields_are_escaped() {
+        let anki = to_anki(&questions());
+        let lines: Vec<&str> = anki.lines().collect();
+        assert_eq!(lines[..3], ["#separator:tab", "#html:true", "#tags column:3"]);
+        let fields: Vec<&str> = lines[3].split('\t').collect();
+        assert_eq!(fields[0], "Which port does &quot;SSH&quot; use?<br><br>A. 21<br>B. 22<br>C. 23, maybe");
+        assert_eq!(fields[1], "<b>B</b>: 22<br><br><i>Hint: Secure shell</i>");
+        assert_eq!(fields[2], "scanning system_hacking");
+        assert!(lines[4].starts_with("Which are &lt;hashes&gt;?"));
+        // untagged, the tag comes from the keyword suggestions
+        assert!(lines[4].ends_with("\tcryptography"));
+        assert!(to_csv(&questions()).lines().nth(2).unwrap().ends_with(",cryptography"));
+    }
+
+    #[test]
+    fn test_csv_and_markdown_import_again() {
+        let original = questions();
+        let from_csv = import::check_entries(import::parse_csv(&to_csv(&original), "out.csv"), &[]);
+        let from_markdown = import::check_entries(import::parse_markdown(&to_markdown(&original), "out.md"), &[]);
+        for report in [from_csv, from_markdown] {
+            assert!(report.malformed.is_empty());
+            let imported: Vec<(String, Vec<String>, String)> =
+                report.accepted.into_iter().map(|a| (a.question.question, a.question.options, a.question.answer)).collect();
+            let expected: Vec<(String, Vec<String>, String)> =
+                original.iter().map(|q| (q.question.clone(), q.options.clone(), q.answer.clone())).collect();
+            assert_eq!(imported, expected);
+        }
+    }
+
+    #[test]
+    fn test_html_has_answer_key() {
+        let html = to_html(&questions());
+        assert!(html.contains("<li class=\"question\">Which are &lt;hashes&gt;?"));
+        assert!(html.contains("<li><b>A, C</b> MD5; SHA-1<div class=\"explanation\">AES is a cipher</div></li>"));
+    }
+}
@@ -1,8 +1,40 @@
 // src/helper/feature_manager.rs
+use std::path::PathBuf;
 use std::process::Command;
-use clap::{Arg, Command as ClapCommand};
+use std::sync::OnceLock;
+use clap::{Arg, ArgAction, Command as ClapCommand};
 use colored::*;
-use anyhow;
+use super::export::ExportFormat;
+use super::import::ImportFormat;
+use super::source::SourceKind;
+/// Percentage needed to pass; the real CEH cut score is around 70%.
+pub const DEFAULT_PASS_MARK: f64 = 70.0;
+
+/// What the binary was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppCommand {
+    Quiz,
+    /// Without files the question in the clipboard is imported.
+    Import { files: Vec<String>, format: Option<ImportFormat>, dry_run: bool },
+    Add,
+    Edit { id: String },
+    /// `format: None` goes by the output extension; `wrong` keeps questions answered wrong before.
+    Export { output: Option<String>, format: Option<ExportFormat>, wrong: bool },
+    /// `None` checks every configured bank.
+    Validate { path: Option<String> },
+    Stats { sessions: usize },
+    Tags { apply: bool },
+    Search { query: String, limit: usize },
+    AiFill { model: String, save: bool },
+    /// Maintainer report of flagged questions; `resolve` clears the flags of one question.
+    Flags { resolve: Option<String> },
+    DbMigrate,
+    DbSync { prune: bool },
+    /// Writes the database back as a bank file; `None` means the primary bank.
+    DbExport { output: Option<String> },
+}
+
+/// Runtime configuration: the parsed command line plus the features compiled into this binary.
 #[derive(Debug)]
 pub struct AppConfig {
     pub use_local: bool,
@@ -11,24 +43,105 @@ pub struct AppConfig {
     pub use_py: bool,
     pub use_db: bool,
     pub use_sqlite: bool,
+    pub pass_mark: f64,
+    pub review: bool,
+    pub exam: bool,
+    pub time_limit: u64,
+    pub domains: Vec<String>,
+    /// Number of quiz questions; `None` asks interactively.
+    pub count: Option<usize>,
+    /// Seed of a quiz session; `None` picks a random one.
+    pub seed: Option<u64>,
+    pub shuffle_options: bool,
+    /// Keep "All/None of the above" last when shuffling.
+    pub pin_options: bool,
+    /// Re-ask wrongly answered questions after the quiz.
+    pub retry_wrong: bool,
+    /// Continue the saved unfinished quiz instead of starting a new one.
+    pub resume: bool,
+    /// Only ask bookmarked questions.
+    pub bookmarked: bool,
+    /// Question banks in precedence order; the first one receives new questions.
+    pub banks: Vec<PathBuf>,
+    /// Where the question pool is read from.
+    pub source: SourceKind,
+    pub command: AppCommand,
 }
 
 impl AppConfig {
     pub fn new(matches: &clap::ArgMatches) -> Self {
+        // quiz options are accepted both after `quiz` and without any subcommand
+        let quiz = matches.subcommand_matches("quiz").unwrap_or(matches);
         AppConfig {
-            use_local: matches.get_flag("use_local"),
-            use_ki: matches.get_flag("use_ki"),
-            use_clipboard: matches.get_flag("use_clipboard"),
-            use_py: matches.get_flag("use_py"),
-            use_db: matches.get_flag("use_db"),
-            use_sqlite: matches.get_flag("use_sqlite"),
+            use_local: super::is_use_local(),
+            use_ki: super::is_use_ki(),
+            use_clipboard: super::is_use_clipboard(),
+            use_py: super::is_use_py(),
+            use_db: super::is_use_db(),
+            use_sqlite: super::is_use_sqlite(),
+            pass_mark: *matches.get_one::<f64>("pass_mark").unwrap_or(&DEFAULT_PASS_MARK),
+            review: quiz.get_flag("review"),
+            exam: quiz.get_flag("exam"),
+            time_limit: *quiz.get_one::<u64>("time_limit").unwrap_or(&240),
+            domains: matches.subcommand_matches("export").unwrap_or(quiz)
+                .get_many::<String>("domain").map(|d| d.cloned().collect()).unwrap_or_default(),
+            count: quiz.get_one::<usize>("count").copied(),
+            seed: quiz.get_one::<u64>("seed").copied(),
+            shuffle_options: !quiz.get_flag("no_shuffle"),
+            pin_options: !quiz.get_flag("no_pin"),
+            retry_wrong: quiz.get_flag("retry_wrong"),
+            resume: quiz.get_flag("resume"),
+            bookmarked: quiz.get_flag("bookmarked"),
+            banks: super::bank::resolve_banks(
+                &matches.get_many::<String>("bank").map(|b| b.cloned().collect::<Vec<_>>()).unwrap_or_default(),
+            ),
+            source: matches.get_one::<SourceKind>("source").cloned().unwrap_or_else(SourceKind::default_for_features),
+            command: match matches.subcommand() {
+                Some(("import", sub)) => AppCommand::Import {
+                    files: sub.get_many::<String>("files").map(|f| f.cloned().collect()).unwrap_or_default(),
+                    format: sub.get_one::<ImportFormat>("format").copied(),
+                    dry_run: sub.get_flag("dry_run"),
+                },
+                Some(("add", _)) => AppCommand::Add,
+                Some(("edit", sub)) => AppCommand::Edit { id: sub.get_one::<String>("id").cloned().unwrap_or_default() },
+                Some(("export", sub)) => AppCommand::Export {
+                    output: sub.get_one::<String>("output").cloned(),
+                    format: sub.get_one::<ExportFormat>("format").copied(),
+                    wrong: sub.get_flag("wrong"),
+                },
+                Some(("validate", sub)) => AppCommand::Validate { path: sub.get_one::<String>("path").cloned() },
+                Some(("stats", sub)) => AppCommand::Stats {
+                    sessions: *sub.get_one::<usize>("sessions").unwrap_or(&10),
+                },
+                Some(("search", sub)) => AppCommand::Search {
+                    query: sub.get_many::<String>("query").map(|q| q.cloned().collect::<Vec<_>>().join(" ")).unwrap_or_default(),
+                    limit: *sub.get_one::<usize>("limit").unwrap_or(&20),
+                },
+                Some(("tags", sub)) => AppCommand::Tags { apply: sub.get_flag("apply") },
+                Some(("ai-fill", sub)) => AppCommand::AiFill {
+                    model: sub.get_one::<String>("model").cloned().unwrap_or_else(|| "mistral-large".to_string()),
+                    save: sub.get_flag("save"),
+                },
+                Some(("flags", sub)) => AppCommand::Flags { resolve: sub.get_one::<String>("resolve").cloned() },
+                Some(("db", sub)) => match sub.subcommand() {
+                    Some(("sync", db)) => AppCommand::DbSync { prune: db.get_flag("prune") },
+                    Some(("export", db)) => AppCommand::DbExport { output: db.get_one::<String>("output").cloned() },
+                    _ => AppCommand::DbMigrate,
+                },
+                _ => AppCommand::Quiz,
+            },
         }
     }
 }
 
-pub fn get_app_config() -> AppConfig {
-    let matches = build_clap_app().get_matches();
-    AppConfig::new(&matches)
+static APP_CONFIG: OnceLock<AppConfig> = OnceLock::new();
+
+/// Parses the command line once; later calls return the same configuration.
+pub fn get_app_config() -> &'static AppConfig {
+    APP_CONFIG.get_or_init(|| {
+        let matches = build_clap_app().get_matches();
+        AppConfig::new(&matches)
+    })
 }
 
 pub fn get_features_description(config: &AppConfig) -> anyhow::Result<Vec<ColoredString>> {
@@ -72,45 +185,278 @@ pub fn banner(config: &AppConfig) -> anyhow::Result<()> {
     "#;
     println!("{}", art);
 
-    let features_description = get_features_description(&config)?;
+    let features_description = get_features_description(config)?;
     let string_descriptions: Vec<String> = features_description.iter().map(ToString::to_string).collect();
     println!("Available features: {}", string_descriptions.join(" -f"));
     Ok(())
 }
 
+/// Options of the `quiz` subcommand; also accepted when no subcommand is given.
+fn quiz_args() -> Vec<Arg> {
+    vec![
+        Arg::new("count")
+            .long("count")
+            .short('n')
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Number of questions (asked interactively if omitted)"),
+        Arg::new("review")
+            .long("review")
+            .action(ArgAction::SetTrue)
+            .help("Spaced-repetition review: ask due and new questions based on past answers"),
+        Arg::new("exam")
+            .long("exam")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("review")
+            .help("Exam simulation: countdown, flag and revisit questions, grading only at the end"),
+        Arg::new("time_limit")
+            .long("time_limit")
+            .value_name("MINUTES")
+            .value_parser(clap::value_parser!(u64))
+            .default_value("240")
+            .help("Time limit of the exam simulation"),
+        Arg::new("domain")
+            .long("domain")
+            .value_name("DOMAIN")
+            .action(ArgAction::Append)
+            .help("Only ask questions tagged with this CEH domain (repeatable, see the tags subcommand)"),
+        Arg::new("bookmarked")
+            .long("bookmarked")
+            .action(ArgAction::SetTrue)
+            .help("Only ask the questions you bookmarked with B at the question prompt"),
+        Arg::new("seed")
+            .long("seed")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64))
+            .help("Seed of the question selection and option order, to repeat a quiz (printed at the end)"),
+        Arg::new("resume")
+            .long("resume")
+            .action(ArgAction::SetTrue)
+            .help("Continue the quiz that was stopped with X or Ctrl-C where you left it"),
+        Arg::new("retry_wrong")
+            .long("retry_wrong")
+            .action(ArgAction::SetTrue)
+            .help("After the quiz, re-ask the wrong answers in new rounds until all are correct (not graded)"),
+        Arg::new("no_shuffle")
+            .long("no_shuffle")
+            .action(ArgAction::SetTrue)
+            .help("Show the options in stored order"),
+        Arg::new("no_pin")
+            .long("no_pin")
+            .action(ArgAction::SetTrue)
+            .help("Shuffle \"All of the above\" / \"None of the above\" like any other option"),
+    ]
+}
+
 pub fn build_clap_app() -> clap::Command {
-    ClapCommand::new("FeatureManager")
+    ClapCommand::new("CEH-mocktest")
         .version("0.5")
         .author("Holger Trahe <holg@github.com>")
-        .about("Manages features for the application")
+        .about("CEH mock test: practice quizzes and question bank tools")
         .arg(
-            Arg::new("use_local")
-                .long("use_local")
-                .help("Enables use_local feature")
+            Arg::new("pass_mark")
+                .long("pass_mark")
+                .value_name("PERCENT")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("70")
+                .global(true)
+                .help("Percentage of correct answers needed to pass")
         )
         .arg(
-            Arg::new("use_ki")
-                .long("use_ki")
-                .help("Enables use_ki feature")
+            Arg::new("bank")
+                .long("bank")
+                .value_name("PATH")
+                .action(ArgAction::Append)
+                .global(true)
+                .help("Question bank file (repeatable; earlier banks win on duplicate ids, new questions go to the first). \
+                       Default: ./questions.json, else $XDG_DATA_HOME/ceh-mocktest/questions.json")
         )
         .arg(
-            Arg::new("use_clipboard")
-                .long("use_clipboard")
-                .help("Enables use_clipboard feature")
+            Arg::new("source")
+                .long("source")
+                .value_name("SOURCE")
+                .value_parser(|s: &str| s.parse::<SourceKind>())
+                .global(true)
+                .help("Where questions are read from: json (the banks), sqlite (questions.db after `db sync`) \
+                       or an http:// URL serving the JSON array. Default: json, or sqlite without use_local")
         )
-        .arg(
-            Arg::new("use_py")
-                .long("use_py")
-                .help("Enables use_py feature")
+        .args(quiz_args())
+        .subcommand(
+            ClapCommand::new("quiz")
+                .about("Run a quiz (the default when no subcommand is given)")
+                .args(quiz_args())
         )
-        .arg(
-            Arg::new("use_db")
-                .long("use_db")
-                .help("Enables use_db feature")
+        .subcommand(
+            ClapCommand::new("import")
+                .about("Add questions from CSV, Markdown or text files, or the one in the clipboard")
+                .arg(
+                    Arg::new("files")
+                        .value_name("FILE")
+                        .num_args(0..)
+                        .help("Files to import (the clipboard if omitted)")
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(|s: &str| s.parse::<ImportFormat>())
+                        .help("csv, markdown or text (guessed from the file extension if omitted)")
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry_run")
+                        .action(ArgAction::SetTrue)
+                        .help("Only print the import report, store nothing")
+                )
         )
-        .arg(
-            Arg::new("use_sqlite")
-                .long("use_sqlite")
-                .help("Enables use_sqlite feature")
+        .subcommand(
+            ClapCommand::new("add")
+                .about("Write a new question interactively, with preview and validation")
+        )
+        .subcommand(
+            ClapCommand::new("edit")
+                .about("Change a question interactively (see `search` for ids)")
+                .arg(
+                    Arg::new("id")
+                        .required(true)
+                        .help("Id of the question to edit")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("export")
+                .about("Write the question bank (filtered by --domain or --wrong) as JSON, Anki TSV, CSV, Markdown or HTML")
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("PATH")
+                        .help("Output file (stdout if omitted)")
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(|s: &str| s.parse::<ExportFormat>())
+                        .help("json, anki, csv, markdown or html (guessed from the output extension if omitted)")
+                )
+                .arg(
+                    Arg::new("domain")
+                        .long("domain")
+                        .visible_alias("tag")
+                        .value_name("DOMAIN")
+                        .action(ArgAction::Append)
+                        .help("Only export questions tagged with this CEH domain or tag (repeatable)")
+                )
+                .arg(
+                    Arg::new("wrong")
+                        .long("wrong")
+                        .action(ArgAction::SetTrue)
+                        .help("Only export questions you answered wrong at least once (use_db)")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("validate")
+                .about("Check the question bank for schema and consistency errors")
+                .arg(
+                    Arg::new("path")
+                        .help("Question bank to check (all configured banks if omitted)")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("stats")
+                .about("Accuracy per CEH domain and difficulty, and the trend of the last sessions")
+                .arg(
+                    Arg::new("sessions")
+                        .long("sessions")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10")
+                        .help("Number of recent sessions to show")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("search")
+                .about("Find questions by keyword or \"quoted phrase\" in question text, options and hints")
+                .arg(
+                    Arg::new("query")
+                        .required(true)
+                        .num_args(1..)
+                        .help("Words that must all match; quote phrases, e.g. '\"zone transfer\"'")
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("20")
+                        .help("Maximum number of results")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("tags")
+                .about("Show questions per CEH domain, including keyword based suggestions for untagged ones")
+                .arg(
+                    Arg::new("apply")
+                        .long("apply")
+                        .action(ArgAction::SetTrue)
+                        .help("Store the suggested tags in the question banks")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("ai-fill")
+                .about("Let Ollama pick the answer and write a hint for the question in the clipboard (use_ki)")
+                .arg(
+                    Arg::new("model")
+                        .long("model")
+                        .value_name("MODEL")
+                        .default_value("mistral-large")
+                        .help("Ollama model to ask")
+                )
+                .arg(
+                    Arg::new("save")
+                        .long("save")
+                        .action(ArgAction::SetTrue)
+                        .help("Add the filled question to the question bank")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("flags")
+                .about("List the questions flagged with F at the question prompt, with reasons and notes")
+                .arg(
+                    Arg::new("resolve")
+                        .long("resolve")
+                        .value_name("ID")
+                        .help("Remove the flags of this question once it is fixed")
+                )
+        )
+        .subcommand(
+            ClapCommand::new("db")
+                .about("Question bank and history database maintenance")
+                .subcommand_required(true)
+                .subcommand(
+                    ClapCommand::new("migrate")
+                        .about("Back-fill stable ids into the question banks and link recorded answers to them")
+                )
+                .subcommand(
+                    ClapCommand::new("sync")
+                        .about("Upsert the question banks into questions.db and report what changed (use_db)")
+                        .arg(
+                            Arg::new("prune")
+                                .long("prune")
+                                .action(ArgAction::SetTrue)
+                                .help("Delete questions from the database that are in no bank anymore")
+                        )
+                )
+                .subcommand(
+                    ClapCommand::new("export")
+                        .about("Write the questions of questions.db as a canonical bank file (use_db)")
+                        .arg(
+                            Arg::new("output")
+                                .long("output")
+                                .short('o')
+                                .value_name("PATH")
+                                .help("Bank file to write (the primary bank if omitted); a configured bank only gets the questions synced from it")
+                        )
+                )
         )
 }
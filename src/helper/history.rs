@@ -0,0 +1,272 @@
+//helper/history.rs
+// Persists every quiz session and every single answer to questions.db next to the
+// primary question bank (use_db feature).
+// Without use_db the History type is a no-op so run_quiz does not need to care.
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::questions::Question;
+use super::quiz::{QuestionResult, QuizReport};
+
+/// One past answer to a question, as far as scheduling is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnswerEvent {
+    pub answered_at: i64,
+    pub correct: bool,
+}
+
+pub fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+#[cfg(feature = "use_db")]
+pub fn open_db() -> anyhow::Result<rusqlite::Connection> {
+    let conn = rusqlite::Connection::open(super::bank::db_path())?;
+    conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+    Ok(conn)
+}
+
+/// Links answers recorded before questions had ids to the matching question of the pool.
+/// Returns the number of answers updated.
+#[cfg(feature = "use_db")]
+pub fn backfill_question_ids(conn: &rusqlite::Connection, pool: &[Question]) -> anyhow::Result<usize> {
+    super::repository::migrate(conn)?;
+    let mut stmt = conn.prepare("UPDATE attempts SET question_id = ?1 WHERE question_id IS NULL AND question = ?2")?;
+    let mut updated = 0;
+    for question in pool.iter().filter(|q| !q.id.is_empty()) {
+        updated += stmt.execute(rusqlite::params![question.id, question.question])?;
+    }
+    Ok(updated)
+}
+
+/// An open quiz session in the history database.
+#[cfg(feature = "use_db")]
+pub struct History {
+    conn: rusqlite::Connection,
+    session_id: i64,
+}
+
+#[cfg(feature = "use_db")]
+impl History {
+    pub fn start(report: &QuizReport) -> anyhow::Result<Self> {
+        Self::start_with_connection(open_db()?, report)
+    }
+
+    pub fn start_with_connection(conn: rusqlite::Connection, report: &QuizReport) -> anyhow::Result<Self> {
+        super::repository::migrate(&conn)?;
+        conn.execute(
+            "INSERT INTO sessions (started_at, pass_mark) VALUES (?1, ?2)",
+            rusqlite::params![unix_seconds(SystemTime::now()), report.pass_mark],
+        )?;
+        let session_id = conn.last_insert_rowid();
+        Ok(History { conn, session_id })
+    }
+
+    /// Continues the unfinished session `id` of a resumed quiz, or starts a new one.
+    pub fn resume(id: Option<i64>, report: &QuizReport) -> anyhow::Result<Self> {
+        let conn = open_db()?;
+        super::repository::migrate(&conn)?;
+        if let Some(id) = id {
+            let open: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM sessions WHERE id = ?1 AND finished_at IS NULL)",
+                [id],
+                |row| row.get(0),
+            )?;
+            if open {
+                return Ok(History { conn, session_id: id });
+            }
+        }
+        Self::start_with_connection(conn, report)
+    }
+
+    pub fn session_id(&self) -> i64 {
+        self.session_id
+    }
+
+    /// The id to store with a saved quiz, see `resume`.
+    pub fn saved_id(&self) -> Option<i64> {
+        Some(self.session_id)
+    }
+
+    pub fn connection(&self) -> &rusqlite::Connection {
+        &self.conn
+    }
+
+    pub fn record_answer(&mut self, result: &QuestionResult) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT INTO attempts (session_id, question_id, question, chosen, correct_answer, outcome, hint_used, answered_at, elapsed_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                self.session_id,
+                result.question_id,
+                result.question,
+                result.chosen,
+                result.correct_answer,
+                format!("{:?}", result.outcome),
+                result.hint_used,
+                unix_seconds(result.answered_at),
+                result.elapsed.as_millis() as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn finish(&mut self, report: &QuizReport) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE sessions SET finished_at = ?1, total = ?2, correct = ?3, wrong = ?4, skipped = ?5, score = ?6, elapsed_ms = ?7
+             WHERE id = ?8",
+            rusqlite::params![
+                unix_seconds(SystemTime::now()),
+                report.total() as i64,
+                report.correct() as i64,
+                report.wrong() as i64,
+                report.skipped() as i64,
+                report.percentage(),
+                report.elapsed.as_millis() as i64,
+                self.session_id,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+/// All recorded answers grouped by question id (question text for answers without one), oldest first.
+#[cfg(feature = "use_db")]
+pub fn load_answer_log() -> anyhow::Result<HashMap<String, Vec<AnswerEvent>>> {
+    let conn = open_db()?;
+    super::repository::migrate(&conn)?;
+    load_answer_log_from(&conn)
+}
+
+#[cfg(feature = "use_db")]
+pub fn load_answer_log_from(conn: &rusqlite::Connection) -> anyhow::Result<HashMap<String, Vec<AnswerEvent>>> {
+    let mut stmt = conn.prepare("SELECT COALESCE(question_id, question), outcome, answered_at FROM attempts ORDER BY answered_at, id")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+    })?;
+    let mut log: HashMap<String, Vec<AnswerEvent>> = HashMap::new();
+    for row in rows {
+        let (question, outcome, answered_at) = row?;
+        log.entry(question).or_default().push(AnswerEvent { answered_at, correct: outcome == "Correct" });
+    }
+    Ok(log)
+}
+
+/// Score of a finished quiz session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    pub started_at: i64,
+    pub total: usize,
+    pub correct: usize,
+    pub score: f64,
+    pub pass_mark: f64,
+}
+
+/// The last `limit` finished sessions, newest first.
+#[cfg(feature = "use_db")]
+pub fn load_sessions(limit: usize) -> anyhow::Result<Vec<SessionSummary>> {
+    let conn = open_db()?;
+    super::repository::migrate(&conn)?;
+    load_sessions_from(&conn, limit)
+}
+
+#[cfg(feature = "use_db")]
+pub fn load_sessions_from(conn: &rusqlite::Connection, limit: usize) -> anyhow::Result<Vec<SessionSummary>> {
+    let mut stmt = conn.prepare(
+        "SELECT started_at, total, correct, score, pass_mark FROM sessions
+         WHERE finished_at IS NOT NULL ORDER BY started_at DESC, id DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map([limit as i64], |row| {
+        Ok(SessionSummary {
+            started_at: row.get(0)?,
+            total: row.get::<_, i64>(1)? as usize,
+            correct: row.get::<_, i64>(2)? as usize,
+            score: row.get::<_, Option<f64>>(3)?.unwrap_or(0.0),
+            pass_mark: row.get(4)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+#[cfg(not(feature = "use_db"))]
+pub fn load_sessions(_limit: usize) -> anyhow::Result<Vec<SessionSummary>> {
+    Ok(vec![])
+}
+
+/// Without use_db there is no history to learn from.
+#[cfg(not(feature = "use_db"))]
+pub fn load_answer_log() -> anyhow::Result<HashMap<String, Vec<AnswerEvent>>> {
+    Ok(HashMap::new())
+}
+
+/// Without use_db nothing is persisted.
+#[cfg(not(feature = "use_db"))]
+pub struct History;
+
+#[cfg(not(feature = "use_db"))]
+impl History {
+    pub fn start(_report: &QuizReport) -> anyhow::Result<Self> {
+        Ok(History)
+    }
+
+    pub fn resume(_id: Option<i64>, _report: &QuizReport) -> anyhow::Result<Self> {
+        Ok(History)
+    }
+
+    pub fn saved_id(&self) -> Option<i64> {
+        None
+    }
+
+    pub fn record_answer(&mut self, _result: &QuestionResult) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    pub fn finish(&mut self, _report: &QuizReport) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "use_db"))]
+mod tests {
+    use super::*;
+    use crate::questions::{Answer, Outcome};
+    use std::time::Duration;
+
+    #[test]
+    fn test_history_persists_session_and_answers() {
+        let mut report = QuizReport::new(70.0);
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        let mut history = History::start_with_connection(conn, &report).unwrap();
+
+        let mut question = Question::new();
+        question.id = "dnssec".to_string();
+        question.question = "What is DNSSEC?".to_string();
+        question.answer = "Signed DNS".to_string();
+        let answer = Answer { outcome: Outcome::Wrong, chosen: Some("Zone transfer".to_string()), hint_used: true };
+        let result = report.record(1, &question, answer, Duration::from_millis(1500)).clone();
+        history.record_answer(&result).unwrap();
+        history.finish(&report).unwrap();
+
+        let (chosen, outcome, hint_used, elapsed): (String, String, bool, i64) = history.connection()
+            .query_row("SELECT chosen, outcome, hint_used, elapsed_ms FROM attempts WHERE session_id = ?1",
+                       [history.session_id()], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap();
+        assert_eq!((chosen.as_str(), outcome.as_str(), hint_used, elapsed), ("Zone transfer", "Wrong", true, 1500));
+
+        let (total, wrong): (i64, i64) = history.connection()
+            .query_row("SELECT total, wrong FROM sessions WHERE id = ?1", [history.session_id()],
+                       |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((total, wrong), (1, 1));
+
+        history.connection().execute("UPDATE attempts SET question_id = NULL", []).unwrap();
+        assert_eq!(backfill_question_ids(history.connection(), &[question.clone()]).unwrap(), 1);
+
+        let sessions = load_sessions_from(history.connection(), 10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].score, 0.0);
+
+        let log = load_answer_log_from(history.connection()).unwrap();
+        assert_eq!(log["dnssec"].len(), 1);
+        assert!(!log["dnssec"][0].correct);
+    }
+}
@@ -31,7 +31,10 @@ pub(crate) fn http_request(url: &str, method: &str, include_body: bool, timeout:
     // Parse the URL
     let url = url::Url::parse(url)?;
     let host = url.host_str().ok_or("Invalid host")?;
-    let path = url.path();
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
     let port = url.port().unwrap_or(80);
 
     // Connect to the server with timeout
@@ -73,12 +76,35 @@ pub(crate) fn http_request(url: &str, method: &str, include_body: bool, timeout:
         }
     }
 
+    let chunked = header_map
+        .iter()
+        .any(|(key, value)| key.eq_ignore_ascii_case("Transfer-Encoding") && value.eq_ignore_ascii_case("chunked"));
+    let body = if chunked { decode_chunked(&body) } else { body };
+
     Ok(HttpResponse {
         status_line,
         headers: header_map,
         body: if include_body { body } else { String::new() },
     })
 }
+
+/// Joins the chunks of a `Transfer-Encoding: chunked` body; HTTP/1.1 servers use it for
+/// responses of unknown length.
+fn decode_chunked(body: &str) -> String {
+    let mut decoded: Vec<u8> = vec![];
+    let mut rest = body.as_bytes();
+    while let Some(line_end) = rest.windows(2).position(|w| w == b"\r\n") {
+        let size_line = String::from_utf8_lossy(&rest[..line_end]);
+        let size = usize::from_str_radix(size_line.split(';').next().unwrap_or("").trim(), 16).unwrap_or(0);
+        let chunk = &rest[line_end + 2..];
+        if size == 0 || chunk.len() < size {
+            break;
+        }
+        decoded.extend_from_slice(&chunk[..size]);
+        rest = chunk[size..].strip_prefix(b"\r\n").unwrap_or(&chunk[size..]);
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
 // Wrapper functions for specific use cases
 pub fn http_request_with_head(url: &str) -> Result<String, Box<dyn std::error::Error>> {
     let response = http_request(url, "GET", false, None/* :Option<Duration> */)?;
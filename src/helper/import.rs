@@ -0,0 +1,436 @@
+//helper/import.rs
+// Bulk import for the `import <FILE>...` subcommand: CSV, Markdown quiz documents and
+// plain-text dumps. Every entry goes through `helper::parser` (CSV cells are resolved
+// directly), is checked against the pool like `check_for_duplicates` does, and ends up
+// accepted, skipped or malformed in the report.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use colored::*;
+use crate::questions::{content_id, is_exact_duplicate, Question};
+use super::parser::{self, ParsedQuestion};
+use super::validate::MAX_OPTIONS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Markdown,
+    Text,
+}
+
+impl ImportFormat {
+    /// `.csv` and `.md` / `.markdown` by extension, anything else is plain text.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("csv") => ImportFormat::Csv,
+            Some("md") | Some("markdown") => ImportFormat::Markdown,
+            _ => ImportFormat::Text,
+        }
+    }
+}
+
+impl FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ImportFormat::Csv),
+            "md" | "markdown" => Ok(ImportFormat::Markdown),
+            "text" | "txt" => Ok(ImportFormat::Text),
+            other => Err(format!("unknown import format '{}', expected csv, markdown or text", other)),
+        }
+    }
+}
+
+impl fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportFormat::Csv => write!(f, "csv"),
+            ImportFormat::Markdown => write!(f, "markdown"),
+            ImportFormat::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// One question found in an input file, `origin` is "file:line".
+pub struct Entry {
+    pub origin: String,
+    pub parsed: anyhow::Result<ParsedQuestion>,
+}
+
+pub struct Accepted {
+    pub origin: String,
+    pub question: Question,
+    pub warnings: Vec<String>,
+}
+
+pub struct Rejected {
+    pub origin: String,
+    pub reason: String,
+}
+
+#[derive(Default)]
+pub struct ImportReport {
+    pub accepted: Vec<Accepted>,
+    /// Duplicates of questions in the pool or earlier in the import.
+    pub skipped: Vec<Rejected>,
+    /// Entries that could not be turned into a question with an answer.
+    pub malformed: Vec<Rejected>,
+}
+
+/// Reads all entries of `path`; the format is guessed from the extension unless given.
+pub fn read_entries(path: &Path, format: Option<ImportFormat>) -> anyhow::Result<Vec<Entry>> {
+    let text = fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Cannot read {}: {}", path.display(), e))?;
+    let name = path.display().to_string();
+    Ok(match format.unwrap_or_else(|| ImportFormat::from_path(path)) {
+        ImportFormat::Csv => parse_csv(&text, &name),
+        ImportFormat::Markdown => parse_markdown(&text, &name),
+        ImportFormat::Text => parse_text(&text, &name),
+    })
+}
+
+/// Splits CSV text into records with the line each one starts on. Handles quoted fields
+/// with `""` escapes and line breaks.
+fn csv_records(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = vec![];
+    let mut record: Vec<String> = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.trim().is_empty()) {
+                    records.push((start, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                start = line;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.trim().is_empty()) {
+        records.push((start, record));
+    }
+    records
+}
+
+/// Column positions of a CSV file.
+struct CsvColumns {
+    question: usize,
+    options: Vec<usize>,
+    answer: Option<usize>,
+    hint: Option<usize>,
+    explanation: Option<usize>,
+    tags: Option<usize>,
+}
+
+impl CsvColumns {
+    /// Without a header: question, A, B, C, D, answer, hint.
+    fn positional() -> Self {
+        CsvColumns { question: 0, options: vec![1, 2, 3, 4], answer: Some(5), hint: Some(6), explanation: None, tags: None }
+    }
+
+    /// A header row names the columns; `None` if the first record is not a header.
+    fn from_header(header: &[String]) -> Option<Self> {
+        let names: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+        let position = |keys: &[&str]| names.iter().position(|n| keys.contains(&n.as_str()));
+        let question = position(&["question", "question text"])?;
+        let options = names
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| {
+                let label = n.strip_prefix("option").unwrap_or(n).trim_start_matches([' ', '_']);
+                n.starts_with("option") || (label.len() == 1 && label.chars().all(|c| c.is_ascii_alphabetic()))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        Some(CsvColumns {
+            question,
+            options,
+            answer: position(&["answer", "correct", "correct answer"]),
+            hint: position(&["hint"]),
+            explanation: position(&["explanation"]),
+            tags: position(&["tags", "tag", "domain"]),
+        })
+    }
+}
+
+/// CSV rows with question, option, answer and hint columns, see `CsvColumns`.
+pub fn parse_csv(text: &str, name: &str) -> Vec<Entry> {
+    let mut records = csv_records(text).into_iter();
+    let mut rows: Vec<(usize, Vec<String>)> = vec![];
+    let columns = match records.next() {
+        Some((line, first)) => match CsvColumns::from_header(&first) {
+            Some(columns) => columns,
+            None => {
+                rows.push((line, first));
+                CsvColumns::positional()
+            }
+        },
+        None => return vec![],
+    };
+    rows.extend(records);
+    rows.into_iter()
+        .map(|(line, row)| Entry { origin: format!("{}:{}", name, line), parsed: csv_question(&row, &columns) })
+        .collect()
+}
+
+fn csv_question(row: &[String], columns: &CsvColumns) -> anyhow::Result<ParsedQuestion> {
+    // cells may contain line breaks, questions are kept on one line
+    let cell = |index: Option<usize>| -> Option<String> {
+        let value = row.get(index?)?.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!value.is_empty()).then_some(value)
+    };
+    let question_text = cell(Some(columns.question)).ok_or_else(|| anyhow::anyhow!("empty question cell"))?;
+    let options: Vec<String> = columns.options.iter().filter_map(|&i| cell(Some(i))).collect();
+    if options.len() < 2 {
+        anyhow::bail!("{} options, at least 2 are needed", options.len());
+    }
+    let mut parsed = ParsedQuestion { question: Question::new(), confidence: 1.0, warnings: vec![] };
+    if options.len() > MAX_OPTIONS {
+        parsed.warnings.push(format!("{} options, more than the {} a question may have", options.len(), MAX_OPTIONS));
+    }
+    let correct = match cell(columns.answer) {
+        Some(spec) => {
+            let correct = parser::resolve_answer(&spec, &options);
+            if correct.is_empty() {
+                anyhow::bail!("answer {:?} does not match any option", spec);
+            }
+            correct
+        }
+        None => anyhow::bail!("no answer"),
+    };
+    let answers: Vec<String> = correct.iter().map(|&i| options[i].clone()).collect();
+    parsed.question = Question {
+        question: question_text,
+        options,
+        hint: cell(columns.hint),
+        explanation: cell(columns.explanation),
+        tags: cell(columns.tags)
+            .map(|t| t.split([',', ';']).map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect())
+            .unwrap_or_default(),
+        ..Question::new()
+    };
+    if answers.len() > 1 {
+        parsed.question.answer = answers.join("; ");
+        parsed.question.answers = answers;
+    } else {
+        parsed.question.answer = answers.into_iter().next().unwrap_or_default();
+    }
+    parsed.question.id = content_id(&parsed.question);
+    Ok(parsed)
+}
+
+/// Markdown quiz documents: every heading or `---` rule starts a question. List items are
+/// options, `- [x]` marks the correct one, `**bold**` markup is dropped.
+pub fn parse_markdown(text: &str, name: &str) -> Vec<Entry> {
+    let mut blocks: Vec<(usize, Vec<String>)> = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        let is_rule = trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-' || c == '*' || c == '_');
+        if trimmed.starts_with('#') || is_rule || blocks.is_empty() {
+            blocks.push((number + 1, vec![]));
+        }
+        if is_rule {
+            continue;
+        }
+        let plain = markdown_line(trimmed);
+        if !plain.is_empty() {
+            blocks.last_mut().unwrap().1.push(plain);
+        }
+    }
+    // a lone title line, e.g. "# CEH practice set", is no question
+    blocks.into_iter()
+        .filter(|(_, lines)| lines.len() > 1)
+        .map(|(line, lines)| Entry { origin: format!("{}:{}", name, line), parsed: parser::parse_question_text(&lines.join("\n")) })
+        .collect()
+}
+
+fn markdown_line(line: &str) -> String {
+    let line = line.trim_start_matches('#').trim_start_matches('>').trim();
+    let item = ["- ", "* ", "+ "].iter().find_map(|bullet| line.strip_prefix(bullet)).map(str::trim_start);
+    let line = match item {
+        Some(item) => match item.get(..3).map(str::to_lowercase).as_deref() {
+            Some("[x]") => format!("*{}", item[3..].trim_start()),
+            Some("[ ]") => item[3..].trim_start().to_string(),
+            _ => item.to_string(),
+        },
+        None => line.to_string(),
+    };
+    line.replace("**", "").replace('`', "")
+}
+
+/// Plain-text dumps: questions separated by blank lines. A block that starts with a lettered
+/// option or an answer/explanation line still belongs to the question before it.
+pub fn parse_text(text: &str, name: &str) -> Vec<Entry> {
+    let mut blocks: Vec<(usize, Vec<&str>)> = vec![];
+    let mut in_block = false;
+    for (number, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.chars().all(|c| c == '-' || c == '=') {
+            in_block = false;
+            continue;
+        }
+        if !in_block && (blocks.is_empty() || !parser::continues_question(trimmed)) {
+            blocks.push((number + 1, vec![]));
+        }
+        in_block = true;
+        blocks.last_mut().unwrap().1.push(trimmed);
+    }
+    blocks.into_iter()
+        .map(|(line, lines)| Entry { origin: format!("{}:{}", name, line), parsed: parser::parse_question_text(&lines.join("\n")) })
+        .collect()
+}
+
+/// Sorts the entries into accepted, skipped and malformed. Duplicates are found the way
+/// `check_for_duplicates` groups them: by question text, then `is_exact_duplicate`.
+pub fn check_entries(entries: Vec<Entry>, pool: &[Question]) -> ImportReport {
+    let mut report = ImportReport::default();
+    let mut by_text: HashMap<String, Vec<Question>> = HashMap::new();
+    for question in pool {
+        by_text.entry(question.question.trim().to_string()).or_default().push(question.clone());
+    }
+    let mut ids: HashSet<String> = pool.iter().map(|q| q.id.clone()).collect();
+    for entry in entries {
+        let parsed = match entry.parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                report.malformed.push(Rejected { origin: entry.origin, reason: e.to_string() });
+                continue;
+            }
+        };
+        let question = parsed.question;
+        if question.answer.is_empty() {
+            report.malformed.push(Rejected { origin: entry.origin, reason: "no answer found".to_string() });
+            continue;
+        }
+        let same_text = by_text.get(question.question.trim()).map(Vec::as_slice).unwrap_or_default();
+        let reason = if let Some(copy) = same_text.iter().find(|q| is_exact_duplicate(q, &question)) {
+            Some(format!("duplicate of {}", copy.id))
+        } else if let Some(other) = same_text.first() {
+            Some(format!("same question text as {} with different options or answer", other.id))
+        } else if ids.contains(&question.id) {
+            Some(format!("id {} is already taken", question.id))
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => report.skipped.push(Rejected { origin: entry.origin, reason }),
+            None => {
+                ids.insert(question.id.clone());
+                by_text.entry(question.question.trim().to_string()).or_default().push(question.clone());
+                report.accepted.push(Accepted { origin: entry.origin, question, warnings: parsed.warnings });
+            }
+        }
+    }
+    report
+}
+
+fn shorten(text: &str) -> String {
+    match text.char_indices().nth(70) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+impl ImportReport {
+    pub fn print(&self) {
+        println!("{}", format!("Accepted ({})", self.accepted.len()).green().bold());
+        for accepted in &self.accepted {
+            println!("  {} {} {}", accepted.origin.dimmed(), format!("[{}]", accepted.question.id).dimmed(), shorten(&accepted.question.question));
+            for warning in &accepted.warnings {
+                println!("      {} {}", "warning".yellow().bold(), warning);
+            }
+        }
+        println!("{}", format!("Skipped ({})", self.skipped.len()).yellow().bold());
+        for skipped in &self.skipped {
+            println!("  {} {}", skipped.origin.dimmed(), skipped.reason);
+        }
+        println!("{}", format!("Malformed ({})", self.malformed.len()).red().bold());
+        for malformed in &self.malformed {
+            println!("  {} {}", malformed.origin.dimmed(), malformed.reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepted(report: &ImportReport) -> Vec<&str> {
+        report.accepted.iter().map(|a| a.question.question.as_str()).collect()
+    }
+
+    #[test]
+    fn test_csv_with_and_without_header() {
+        let csv = "Which port does SSH use?,21,22,23,25,B,Secure shell\n\"Pick the \"\"hashes\"\"\",MD5,AES,SHA-1,,\"A, C\",\n";
+        let entries = parse_csv(csv, "q.csv");
+        let report = check_entries(entries, &[]);
+        assert_eq!(accepted(&report), vec!["Which port does SSH use?", "Pick the \"hashes\""]);
+        assert_eq!(report.accepted[0].question.answer, "22");
+        assert_eq!(report.accepted[0].question.hint.as_deref(), Some("Secure shell"));
+        assert_eq!(report.accepted[1].question.options, vec!["MD5", "AES", "SHA-1"]);
+        assert_eq!(report.accepted[1].question.answers, vec!["MD5", "SHA-1"]);
+
+        let csv = "question,option_a,option_b,answer,tags\n\"What does\nDNSSEC add?\",Signatures,Encryption,Signatures,dns;Cryptography\nNo answer,x,y,,\n";
+        let report = check_entries(parse_csv(csv, "q.csv"), &[]);
+        assert_eq!(accepted(&report), vec!["What does DNSSEC add?"]);
+        assert_eq!(report.accepted[0].question.tags, vec!["dns", "cryptography"]);
+        assert_eq!(report.malformed[0].origin, "q.csv:4");
+        assert_eq!(report.malformed[0].reason, "no answer");
+    }
+
+    #[test]
+    fn test_markdown_document() {
+        let md = "# CEH practice set\n\n## Question 1\nWhich scan sends no flags?\n\n- A. XMAS scan\n- B. NULL scan\n\n**Answer:** B\n\n---\n\nWhich are hashes?\n- [x] MD5\n- [ ] AES\n- [x] SHA-1\n\n## Broken\nJust text\n";
+        let report = check_entries(parse_markdown(md, "q.md"), &[]);
+        assert_eq!(accepted(&report), vec!["Which scan sends no flags?", "Which are hashes?"]);
+        assert_eq!(report.accepted[0].question.answer, "NULL scan");
+        assert_eq!(report.accepted[1].question.answers, vec!["MD5", "SHA-1"]);
+        assert_eq!(report.malformed.len(), 1);
+        assert_eq!(report.malformed[0].origin, "q.md:18");
+    }
+
+    #[test]
+    fn test_text_dump_blocks_and_duplicates() {
+        let text = "1. Which port does SSH use?\nA. 21\nB. 22\n\nAnswer: B\n\n2. Which port does SSH use?\nA. 21\nB. 22\nAnswer: B\n\nWhich port does Telnet use?\nA. 22\nB. 23\n\nWhich port does SMTP use?\nA) 25\nB) 110\nAnswer: A\n";
+        let existing = Question {
+            id: "smtp".to_string(),
+            question: "Which port does SMTP use?".to_string(),
+            options: vec!["25".to_string(), "587".to_string()],
+            answer: "25".to_string(),
+            ..Question::new()
+        };
+        let report = check_entries(parse_text(text, "dump.txt"), &[existing]);
+        assert_eq!(accepted(&report), vec!["Which port does SSH use?"]);
+        let skipped: Vec<(&str, &str)> = report.skipped.iter().map(|s| (s.origin.as_str(), s.reason.as_str())).collect();
+        let first_id = report.accepted[0].question.id.clone();
+        assert_eq!(skipped, vec![
+            ("dump.txt:7", format!("duplicate of {}", first_id).as_str()),
+            ("dump.txt:16", "same question text as smtp with different options or answer"),
+        ]);
+        assert_eq!(report.malformed.len(), 1);
+        assert_eq!(report.malformed[0].reason, "no answer found");
+    }
+}
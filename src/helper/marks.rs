@@ -0,0 +1,227 @@
+//helper/marks.rs
+// Bookmarks ("review later") and flags ("answer looks wrong", "typo", "outdated") set from
+// the question prompt. They are kept in bookmarks.json next to the primary bank, so they
+// work without the use_db feature; bookmarks feed `quiz --bookmarked`, flags the `flags` report.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use crate::questions::Question;
+use super::bank;
+use super::history::unix_seconds;
+use super::stats::format_timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagReason {
+    WrongAnswer,
+    Typo,
+    Outdated,
+    Other,
+}
+
+impl FlagReason {
+    pub const ALL: [FlagReason; 4] = [FlagReason::WrongAnswer, FlagReason::Typo, FlagReason::Outdated, FlagReason::Other];
+}
+
+impl fmt::Display for FlagReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlagReason::WrongAnswer => write!(f, "answer looks wrong"),
+            FlagReason::Typo => write!(f, "typo"),
+            FlagReason::Outdated => write!(f, "outdated"),
+            FlagReason::Other => write!(f, "other"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub question_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Flag {
+    pub question_id: String,
+    pub reason: FlagReason,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marks {
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    pub flags: Vec<Flag>,
+}
+
+impl Marks {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Marks::default());
+        }
+        let text = fs::read_to_string(path)?;
+        serde_json::from_str(&text).map_err(|e| anyhow::anyhow!("Unable to parse {}: {}", path.display(), e))
+    }
+
+    pub fn is_bookmarked(&self, question_id: &str) -> bool {
+        self.bookmarks.iter().any(|b| b.question_id == question_id)
+    }
+
+    /// Adds the bookmark or removes an existing one; returns whether it is bookmarked now.
+    pub fn toggle_bookmark(&mut self, question_id: &str, note: Option<String>) -> bool {
+        if self.is_bookmarked(question_id) {
+            self.bookmarks.retain(|b| b.question_id != question_id);
+            false
+        } else {
+            self.bookmarks.push(Bookmark { question_id: question_id.to_string(), note, created_at: now() });
+            true
+        }
+    }
+
+    pub fn add_flag(&mut self, question_id: &str, reason: FlagReason, note: Option<String>) {
+        self.flags.push(Flag { question_id: question_id.to_string(), reason, note, created_at: now() });
+    }
+
+    /// Removes all flags of a question once it was fixed; returns how many there were.
+    pub fn resolve_flags(&mut self, question_id: &str) -> usize {
+        let before = self.flags.len();
+        self.flags.retain(|f| f.question_id != question_id);
+        before - self.flags.len()
+    }
+
+    /// Flags grouped by question, the most often flagged first.
+    pub fn flags_by_question(&self) -> Vec<(&str, Vec<&Flag>)> {
+        let mut grouped: BTreeMap<&str, Vec<&Flag>> = BTreeMap::new();
+        for flag in &self.flags {
+            grouped.entry(flag.question_id.as_str()).or_default().push(flag);
+        }
+        let mut grouped: Vec<(&str, Vec<&Flag>)> = grouped.into_iter().collect();
+        grouped.sort_by_key(|(_, flags)| std::cmp::Reverse(flags.len()));
+        grouped
+    }
+}
+
+fn now() -> i64 {
+    unix_seconds(SystemTime::now())
+}
+
+pub fn load_marks() -> anyhow::Result<Marks> {
+    Marks::load(&bank::marks_path())
+}
+
+/// Read-modify-write of bookmarks.json under the same lock and atomic write as the banks.
+pub fn modify_marks<T>(change: impl FnOnce(&mut Marks) -> T) -> anyhow::Result<T> {
+    let path = bank::marks_path();
+    let _lock = bank::lock_bank(&path)?;
+    let mut marks = Marks::load(&path)?;
+    let result = change(&mut marks);
+    bank::write_atomic(&path, serde_json::to_string_pretty(&marks)?.as_bytes())?;
+    Ok(result)
+}
+
+fn read_line(prompt: &str) -> String {
+    print!("{}", prompt);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
+
+fn read_note() -> Option<String> {
+    Some(read_line("Note (optional): ")).filter(|n| !n.is_empty())
+}
+
+/// The B key of the question prompt: bookmarks with an optional note, or removes the bookmark.
+pub fn prompt_bookmark(question_id: &str) {
+    let bookmarked = load_marks().map(|m| m.is_bookmarked(question_id)).unwrap_or(false);
+    let note = if bookmarked { None } else { read_note() };
+    match modify_marks(|marks| marks.toggle_bookmark(question_id, note)) {
+        Ok(true) => println!("{}", "Bookmarked, practice it with `quiz --bookmarked`.".cyan()),
+        Ok(false) => println!("{}", "Bookmark removed.".cyan()),
+        Err(e) => eprintln!("Could not save the bookmark: {}", e),
+    }
+}
+
+/// The F key of the question prompt: reports the question to the maintainers.
+pub fn prompt_flag(question_id: &str) {
+    let choices: Vec<String> = FlagReason::ALL.iter().enumerate().map(|(i, r)| format!("{} {}", i + 1, r)).collect();
+    let reason = match read_line(&format!("Flag as ({}, Enter cancels): ", choices.join(", "))).parse::<usize>() {
+        Ok(n) if (1..=FlagReason::ALL.len()).contains(&n) => FlagReason::ALL[n - 1],
+        _ => {
+            println!("Not flagged.");
+            return;
+        }
+    };
+    let note = read_note();
+    match modify_marks(|marks| marks.add_flag(question_id, reason, note)) {
+        Ok(()) => println!("{}", "Flagged, thanks for reporting it.".cyan()),
+        Err(e) => eprintln!("Could not save the flag: {}", e),
+    }
+}
+
+/// The maintainer report: every flagged question with the reasons and notes.
+pub fn print_flag_report(marks: &Marks, pool: &[Question]) {
+    let grouped = marks.flags_by_question();
+    if grouped.is_empty() {
+        println!("{}", "No flagged questions.".green());
+        return;
+    }
+    for (id, flags) in &grouped {
+        println!("\n{} {}", format!("[{}]", id).dimmed(), format!("{} flags", flags.len()).yellow());
+        match pool.iter().find(|q| q.id == *id) {
+            Some(question) => {
+                println!("  {}", question.question);
+                println!("  Stored answer: {}", question.correct_answers().join("; ").green());
+            }
+            None => println!("  {}", "(no longer in the question bank)".dimmed()),
+        }
+        for flag in flags {
+            let note = flag.note.as_deref().map(|n| format!(": {}", n)).unwrap_or_default();
+            println!("  - {} {}{}", format_timestamp(flag.created_at).dimmed(), flag.reason.to_string().bold(), note);
+        }
+    }
+    println!("\n{} flagged questions, {} flags. `flags --resolve <id>` removes them after a fix.", grouped.len(), marks.flags.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_bookmark_and_resolve_flags() {
+        let mut marks = Marks::default();
+        assert!(marks.toggle_bookmark("a", Some("subnetting".to_string())));
+        assert!(marks.is_bookmarked("a"));
+        assert!(!marks.toggle_bookmark("a", None));
+        assert!(!marks.is_bookmarked("a"));
+
+        marks.add_flag("a", FlagReason::Typo, None);
+        marks.add_flag("b", FlagReason::WrongAnswer, Some("should be C".to_string()));
+        marks.add_flag("b", FlagReason::Outdated, None);
+        let grouped: Vec<(&str, usize)> = marks.flags_by_question().into_iter().map(|(id, f)| (id, f.len())).collect();
+        assert_eq!(grouped, vec![("b", 2), ("a", 1)]);
+        assert_eq!(marks.resolve_flags("b"), 2);
+        assert_eq!(marks.flags.len(), 1);
+    }
+
+    #[test]
+    fn test_marks_json_format() {
+        let marks = Marks {
+            bookmarks: vec![],
+            flags: vec![Flag { question_id: "a".to_string(), reason: FlagReason::WrongAnswer, note: None, created_at: 1 }],
+        };
+        let json = serde_json::to_string(&marks).unwrap();
+        assert_eq!(json, r#"{"bookmarks":[],"flags":[{"question_id":"a","reason":"wrong_answer","created_at":1}]}"#);
+        assert_eq!(serde_json::from_str::<Marks>(&json).unwrap(), marks);
+    }
+}
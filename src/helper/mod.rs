@@ -4,13 +4,31 @@ use colored::*;
 pub mod htr_low_level_http;
 pub mod ollama;
 pub mod quiz;
+pub mod history;
+pub mod review;
+pub mod exam;
+pub mod domains;
+pub mod stats;
+pub mod commands;
+pub mod validate;
+pub mod bank;
+pub mod source;
+pub mod repository;
+pub mod search;
+pub mod editor;
+pub mod parser;
+pub mod import;
+pub mod export;
+pub mod shuffle;
+pub mod session;
+pub mod marks;
 mod ceh_lab;
 mod json_to_sqlite;
 mod feature_manager;
-pub use feature_manager::{AppConfig, build_clap_app, get_app_config};
+pub use feature_manager::{AppCommand, AppConfig, DEFAULT_PASS_MARK, banner, build_clap_app, get_app_config};
 
-#[cfg(feature = "use_sqlite")]
-pub use json_to_sqlite::json_to_sqlite;
+#[cfg(feature = "use_db")]
+pub use json_to_sqlite::{json_to_sqlite, sqlite_to_json};
 use crate::helper;
 
 #[cfg(not(feature = "use_local"))]
@@ -0,0 +1,429 @@
+//helper/parser.rs
+// Turns pasted question text into a `Question`. Recognised layouts:
+//   - options marked "A. x", "A) x", "(a) x", "1. x", "1) x", continuation lines allowed
+//   - the exam portal copy: a line "A", the option, a line "B", the option, ...
+//   - unmarked options on consecutive lines after the question (lower confidence)
+// The answer comes from a "*" on the option(s) or an "Answer: C" / "Answer: A, C" /
+// "Answer: <option text>" line; "Explanation:" and "Hint:" lines are picked up as well.
+use crate::questions::{content_id, Question};
+use super::validate::{MAX_OPTIONS, MIN_OPTIONS};
+
+/// Result of parsing a pasted question.
+#[derive(Debug, Clone)]
+pub struct ParsedQuestion {
+    pub question: Question,
+    /// 0.0 to 1.0; below `LOW_CONFIDENCE` the result should be reviewed before saving.
+    pub confidence: f64,
+    pub warnings: Vec<String>,
+}
+
+pub const LOW_CONFIDENCE: f64 = 0.7;
+
+impl ParsedQuestion {
+    pub fn is_confident(&self) -> bool {
+        self.confidence >= LOW_CONFIDENCE
+    }
+
+    fn warn(&mut self, penalty: f64, warning: String) {
+        self.confidence = (self.confidence - penalty).max(0.0);
+        self.warnings.push(warning);
+    }
+}
+
+/// An option line: its position from the label (A/a/1 = 0) and the text after the label.
+#[derive(Debug, PartialEq)]
+struct Marker<'a> {
+    index: usize,
+    numeric: bool,
+    /// "* C. text": the correct option marked in front of the label.
+    starred: bool,
+    text: &'a str,
+}
+
+/// How the body of a paste splits into question lines and options.
+struct Split {
+    question: Vec<String>,
+    options: Vec<String>,
+    /// Confidence penalties with their warnings, applied if this layout is used.
+    warnings: Vec<(f64, String)>,
+}
+
+/// Recognises "A. text", "A) text", "(A) text", "a: text", "1. text" and "1) text".
+fn option_marker(line: &str) -> Option<Marker<'_>> {
+    let starred = line.starts_with('*');
+    let line = line.trim_start_matches('*').trim_start();
+    let rest = line.strip_prefix('(').unwrap_or(line);
+    let label_len = rest.chars().take_while(|c| c.is_ascii_alphanumeric()).count();
+    if label_len == 0 || label_len > 2 {
+        return None;
+    }
+    let (label, after) = rest.split_at(label_len);
+    let mut after_chars = after.chars();
+    if !matches!(after_chars.next(), Some('.' | ')' | ':' | ']')) {
+        return None;
+    }
+    let text = after_chars.as_str();
+    if !text.starts_with(char::is_whitespace) || text.trim().is_empty() {
+        return None;
+    }
+    let (index, numeric) = match label.parse::<usize>() {
+        Ok(number) if (1..=MAX_OPTIONS).contains(&number) => (number - 1, true),
+        Ok(_) => return None,
+        Err(_) if label.len() == 1 => (letter_index(label.chars().next()?)?, false),
+        Err(_) => return None,
+    };
+    Some(Marker { index, numeric, starred, text: text.trim() })
+}
+
+fn letter_index(letter: char) -> Option<usize> {
+    let index = (letter.to_ascii_uppercase() as u8).checked_sub(b'A')? as usize;
+    (letter.is_ascii_alphabetic() && index < MAX_OPTIONS).then_some(index)
+}
+
+/// The bare letter lines of the exam portal copy: `A`, `B`, ... or `"A,"`.
+fn bare_letter(line: &str) -> Option<usize> {
+    let letter = line.trim_matches(|c| c == '"' || c == ',');
+    let mut chars = letter.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_uppercase() => letter_index(c),
+        _ => None,
+    }
+}
+
+/// Strips a "*" or "(correct)" marking from an option; returns the clean text and whether it was marked.
+fn strip_correct_mark(text: &str) -> (String, bool) {
+    let trimmed = text.trim();
+    for mark in ["(correct)", "[correct]", "✓", "✔", "*"] {
+        if let Some(rest) = trimmed.strip_suffix(mark) {
+            return (rest.trim().to_string(), true);
+        }
+    }
+    match trimmed.strip_prefix('*') {
+        Some(rest) => (rest.trim().to_string(), true),
+        None => (trimmed.to_string(), false),
+    }
+}
+
+/// The value of lines like "Answer: C" or "Explanation - because" for one of `keys`.
+fn labelled<'a>(line: &'a str, keys: &[&str]) -> Option<&'a str> {
+    for key in keys {
+        if line.get(..key.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(key)) {
+            let rest = line[key.len()..].trim_start();
+            if let Some(value) = rest.strip_prefix(':').or_else(|| rest.strip_prefix('-')).or_else(|| rest.strip_prefix('=')) {
+                return Some(value.trim());
+            }
+        }
+    }
+    None
+}
+
+const ANSWER_KEYS: &[&str] = &["correct answers", "correct answer", "answers", "answer", "ans"];
+const EXPLANATION_KEYS: &[&str] = &["explanation", "rationale", "reason"];
+const HINT_KEYS: &[&str] = &["hint"];
+
+/// Whether `line` can only belong to the question before it: a lettered option, a bare
+/// "A" line of the exam portal layout, or an answer, explanation or hint line.
+pub fn continues_question(line: &str) -> bool {
+    let line = line.trim();
+    option_marker(line).is_some_and(|m| !m.numeric)
+        || bare_letter(line).is_some()
+        || [ANSWER_KEYS, EXPLANATION_KEYS, HINT_KEYS].iter().any(|keys| labelled(line, keys).is_some())
+}
+
+/// Removes a "Question 12:", "Q12." or "12." prefix from the question text.
+fn strip_question_number(text: &str) -> &str {
+    let (rest, prefixed) = if text.get(..8).is_some_and(|p| p.eq_ignore_ascii_case("question")) {
+        (&text[8..], true)
+    } else if text.starts_with(['q', 'Q']) && text[1..].starts_with(|c: char| c.is_ascii_digit()) {
+        (&text[1..], true)
+    } else {
+        (text, false)
+    };
+    let rest = rest.trim_start();
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return text;
+    }
+    let after = &rest[digits..];
+    match after.strip_prefix(['.', ':', ')']) {
+        Some(stripped) => stripped.trim_start(),
+        // "12 hosts ..." is text, "Question 12 Which ..." is a number
+        None if prefixed && after.starts_with(char::is_whitespace) => after.trim_start(),
+        None => text,
+    }
+}
+
+/// Parses one pasted question. Fails only if no question with at least two options can be
+/// found; everything else lowers the confidence and adds a warning.
+pub fn parse_question_text(text: &str) -> anyhow::Result<ParsedQuestion> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+
+    // trailer lines: answer, explanation (which may continue over the following lines) and hint
+    let mut body = vec![];
+    let mut answer_spec: Option<String> = None;
+    let mut explanation: Vec<&str> = vec![];
+    let mut hint: Option<String> = None;
+    let mut in_explanation = false;
+    for line in &lines {
+        if let Some(value) = labelled(line, ANSWER_KEYS) {
+            answer_spec = Some(value.to_string());
+            in_explanation = false;
+        } else if let Some(value) = labelled(line, EXPLANATION_KEYS) {
+            explanation.push(value);
+            in_explanation = true;
+        } else if let Some(value) = labelled(line, HINT_KEYS) {
+            hint = Some(value.to_string());
+            in_explanation = false;
+        } else if in_explanation {
+            explanation.push(line);
+        } else {
+            body.push(*line);
+        }
+    }
+
+    let split = split_marked(&body)
+        .or_else(|| split_bare_letters(&body))
+        .or_else(|| split_consecutive(&body))
+        .ok_or_else(|| anyhow::anyhow!("no question with at least {} options found", MIN_OPTIONS))?;
+    let mut parsed = ParsedQuestion { question: Question { options: vec![], ..Question::new() }, confidence: 1.0, warnings: vec![] };
+    for (penalty, warning) in split.warnings {
+        parsed.warn(penalty, warning);
+    }
+
+    let question_text = split.question.join(" ");
+    let question_text = strip_question_number(&question_text).to_string();
+    if question_text.is_empty() {
+        anyhow::bail!("no question text found before the options");
+    }
+
+    let mut options = vec![];
+    let mut starred = vec![];
+    for (index, option) in split.options.iter().enumerate() {
+        let (clean, marked) = strip_correct_mark(option);
+        if marked {
+            starred.push(index);
+        }
+        options.push(clean);
+    }
+    if options.len() > MAX_OPTIONS {
+        parsed.warn(0.3, format!("{} options, more than the {} a question may have", options.len(), MAX_OPTIONS));
+    }
+
+    let mut correct = starred;
+    if let Some(spec) = &answer_spec {
+        let from_line = resolve_answer(spec, &options);
+        if from_line.is_empty() {
+            parsed.warn(0.4, format!("answer {:?} does not match any option", spec));
+        } else if !correct.is_empty() && correct != from_line {
+            parsed.warn(0.3, "the marked option and the answer line disagree, using the answer line".to_string());
+        }
+        if !from_line.is_empty() {
+            correct = from_line;
+        }
+    }
+    if correct.is_empty() {
+        parsed.warn(0.3, "no answer found (mark it with * or add \"Answer: C\")".to_string());
+    }
+
+    let answers: Vec<String> = correct.iter().map(|&i| options[i].clone()).collect();
+    parsed.question.question = question_text;
+    parsed.question.options = options;
+    if answers.len() > 1 {
+        parsed.question.answer = answers.join("; ");
+        parsed.question.answers = answers;
+    } else {
+        parsed.question.answer = answers.into_iter().next().unwrap_or_default();
+    }
+    parsed.question.hint = hint;
+    if !explanation.is_empty() {
+        parsed.question.explanation = Some(explanation.join(" "));
+    }
+    parsed.question.id = content_id(&parsed.question);
+    Ok(parsed)
+}
+
+/// Options marked "A." / "1)" etc.: the question is everything before the option labelled
+/// A or 1; lines without a label continue the previous option. A numbered question followed
+/// by lettered options ("1. Which ...?" "A. ...") starts at the lettered ones.
+fn split_marked(body: &[&str]) -> Option<Split> {
+    let markers: Vec<Option<Marker>> = body.iter().map(|l| option_marker(l)).collect();
+    let starts_list = |start: usize| {
+        let first = markers[start].as_ref()?;
+        let next = markers[start + 1..].iter().flatten().next()?;
+        (first.index == 0 && next.index == 1 && next.numeric == first.numeric).then_some(start)
+    };
+    let start = (0..body.len()).rev().find_map(starts_list)?;
+    let mut options: Vec<String> = vec![];
+    let mut warnings = vec![];
+    for (line, marker) in body[start..].iter().zip(&markers[start..]) {
+        match marker {
+            Some(marker) => {
+                if marker.index != options.len() {
+                    warnings.push((0.2, format!("option {} is labelled {}", options.len() + 1, marker.index + 1)));
+                }
+                // keep the star for strip_correct_mark
+                options.push(if marker.starred { format!("*{}", marker.text) } else { marker.text.to_string() });
+            }
+            None => {
+                let last = options.last_mut()?;
+                last.push(' ');
+                last.push_str(line);
+            }
+        }
+    }
+    (options.len() >= MIN_OPTIONS).then(|| Split { question: to_strings(&body[..start]), options, warnings })
+}
+
+fn to_strings(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|l| l.to_string()).collect()
+}
+
+/// The exam portal copy: question lines, then "A", option, "B", option, ...
+fn split_bare_letters(body: &[&str]) -> Option<Split> {
+    let start = body.iter().position(|l| bare_letter(l) == Some(0))?;
+    let mut options: Vec<String> = vec![];
+    let mut expect_letter = true;
+    for line in &body[start..] {
+        if expect_letter && bare_letter(line) == Some(options.len()) {
+            expect_letter = false;
+        } else if expect_letter {
+            // a second line of the previous option
+            let last = options.last_mut()?;
+            last.push(' ');
+            last.push_str(line);
+        } else {
+            options.push(line.to_string());
+            expect_letter = true;
+        }
+    }
+    (options.len() >= MIN_OPTIONS).then(|| Split { question: to_strings(&body[..start]), options, warnings: vec![] })
+}
+
+/// No labels at all: the question runs up to the last line ending in "?" (or is the first
+/// line), every following line is an option.
+fn split_consecutive(body: &[&str]) -> Option<Split> {
+    let end = body.iter().rposition(|l| l.ends_with('?')).map(|i| i + 1).unwrap_or(1);
+    if body.len() < end + MIN_OPTIONS {
+        return None;
+    }
+    Some(Split {
+        question: to_strings(&body[..end]),
+        options: to_strings(&body[end..]),
+        warnings: vec![(0.35, "options have no labels, assumed one option per line after the question".to_string())],
+    })
+}
+
+/// Option indexes named by an answer line: letters ("C", "A, C", "A and C"), numbers ("3")
+/// or the option text itself.
+pub fn resolve_answer(spec: &str, options: &[String]) -> Vec<usize> {
+    let spec = spec.trim().trim_end_matches('.');
+    if let Some(index) = options.iter().position(|o| o.eq_ignore_ascii_case(spec)) {
+        return vec![index];
+    }
+    // "C. the option text" names the option by its label
+    if let Some(marker) = option_marker(spec) {
+        if marker.index < options.len() {
+            return vec![marker.index];
+        }
+    }
+    let mut indexes = vec![];
+    for part in spec.split(|c: char| c == ',' || c == '&' || c == '/' || c.is_whitespace()).filter(|p| !p.is_empty()) {
+        if part.eq_ignore_ascii_case("and") {
+            continue;
+        }
+        let part = part.trim_matches(|c| c == '(' || c == ')' || c == '.');
+        let index = match part.parse::<usize>() {
+            Ok(number) if number >= 1 => Some(number - 1),
+            Ok(_) => None,
+            Err(_) if part.len() == 1 => part.chars().next().and_then(letter_index),
+            Err(_) => None,
+        };
+        match index {
+            Some(index) if index < options.len() => {
+                if !indexes.contains(&index) {
+                    indexes.push(index);
+                }
+            }
+            _ => return vec![],
+        }
+    }
+    indexes.sort_unstable();
+    indexes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lettered_options_with_answer_line() {
+        let parsed = parse_question_text(
+            "Question 12: Which tool is used for\nDNS enumeration?\nA. Nmap\nB) dnsenum\nC. Hydra\nD. John\nAnswer: B\nExplanation: dnsenum queries\nDNS servers.",
+        ).unwrap();
+        let q = &parsed.question;
+        assert_eq!(q.question, "Which tool is used for DNS enumeration?");
+        assert_eq!(q.options, vec!["Nmap", "dnsenum", "Hydra", "John"]);
+        assert_eq!(q.answer, "dnsenum");
+        assert_eq!(q.explanation.as_deref(), Some("dnsenum queries DNS servers."));
+        assert!(parsed.warnings.is_empty());
+        assert_eq!(parsed.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_numbered_options_with_starred_answers() {
+        let parsed = parse_question_text("Select the hashes:\n1) MD5 *\n2) AES\n3) *SHA-256\nHint: one-way").unwrap();
+        let q = &parsed.question;
+        assert_eq!(q.options, vec!["MD5", "AES", "SHA-256"]);
+        assert_eq!(q.answers, vec!["MD5", "SHA-256"]);
+        assert_eq!(q.hint.as_deref(), Some("one-way"));
+        assert!(parsed.is_confident());
+    }
+
+    #[test]
+    fn test_exam_portal_layout() {
+        let parsed = parse_question_text("What does SSH encrypt?\nA\nOnly passwords\nB\nThe whole session\nC\nNothing\nD\nOnly files\nAnswer: B").unwrap();
+        assert_eq!(parsed.question.options.len(), 4);
+        assert_eq!(parsed.question.answer, "The whole session");
+        assert!(parsed.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_unlabelled_options_lower_confidence() {
+        let parsed = parse_question_text("Which port does SSH use?\n21\n22\n23\nAnswer: 22").unwrap();
+        assert_eq!(parsed.question.options, vec!["21", "22", "23"]);
+        assert_eq!(parsed.question.answer, "22");
+        assert!(!parsed.is_confident());
+        assert_eq!(parsed.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_missing_or_unknown_answer_is_a_warning() {
+        let parsed = parse_question_text("Which port?\n(a) 21\n(b) 22\nAnswer: E").unwrap();
+        assert!(parsed.question.answer.is_empty());
+        assert_eq!(parsed.warnings.len(), 2);
+        assert!(!parsed.is_confident());
+        assert!(parse_question_text("Just a line of text").is_err());
+    }
+
+    #[test]
+    fn test_numbered_question_with_lettered_options() {
+        let parsed = parse_question_text("7. Which scan sends no flags?\nA. XMAS scan\nB. NULL scan\n* C. FIN scan\nAnswer: B").unwrap();
+        assert_eq!(parsed.question.question, "Which scan sends no flags?");
+        assert_eq!(parsed.question.options, vec!["XMAS scan", "NULL scan", "FIN scan"]);
+        assert_eq!(parsed.question.answer, "NULL scan");
+        assert_eq!(parsed.warnings, vec!["the marked option and the answer line disagree, using the answer line"]);
+        assert_eq!(strip_question_number("12 hosts answer the ping?"), "12 hosts answer the ping?");
+        assert_eq!(strip_question_number("Q3) Which?"), "Which?");
+    }
+
+    #[test]
+    fn test_resolve_answer_variants() {
+        let options: Vec<String> = ["Nmap", "dnsenum", "Hydra"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(resolve_answer("A and C", &options), vec![0, 2]);
+        assert_eq!(resolve_answer("c, a", &options), vec![0, 2]);
+        assert_eq!(resolve_answer("2", &options), vec![1]);
+        assert_eq!(resolve_answer("hydra", &options), vec![2]);
+        assert_eq!(resolve_answer("B. dnsenum", &options), vec![1]);
+        assert!(resolve_answer("Z", &options).is_empty());
+    }
+}
@@ -2,16 +2,27 @@
 // ./helpers/mod.rs
 #![allow(unused_imports, dead_code)]
 use crate::questions;
-use crate::questions::TypedQuestion;
+use crate::questions::{Answer, Difficulty, Outcome, Question, TypedQuestion};
 use std::io::{self, Write};
-use rand::prelude::{IndexedRandom};
-use super::{banner, quiz}; // The mod.rs is the best explanation for super IMHO
+use std::time::{Duration, Instant, SystemTime};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use rand::prelude::{IndexedRandom, SliceRandom};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use super::{banner, get_app_config, quiz, AppConfig, DEFAULT_PASS_MARK}; // The mod.rs is the best explanation for super IMHO
+use super::history::{self, History};
+use super::exam::{self, EXAM_TIME_LIMIT};
+use super::{domains, marks, review, stats};
+use super::session::{self, SavedSession};
+use super::shuffle::{Presentation, Shuffle};
+use super::stats::Breakdown;
 pub fn cls() {
     print!("{esc}[2J{esc}[1;1H", esc = 27 as char); // Clear screen
 }
 pub fn get_num_questions() -> Option<usize> {
     cls();
-    let _ = banner().expect("Failed to print banner");
+    banner(get_app_config()).expect("Failed to print banner");
     loop {
         print!("Do you want to set a custom number of questions? [Default: 125] (yes/no): ");
         io::stdout().flush().unwrap();
@@ -32,28 +43,522 @@ pub fn get_num_questions() -> Option<usize> {
             }
             "no" => return None,
             "cls" => cls(),
-            "banner" => banner().expect("Failed to print banner"),
+            "banner" => banner(get_app_config()).expect("Failed to print banner"),
             _ => return None,
         }
     }
 }
 
-pub fn run_quiz(question_pool: Vec<TypedQuestion>, num_questions: Option<usize>) {
-    let num_questions = num_questions.unwrap_or(125);
-    let mut rng = rand::thread_rng();
-    let mut selected_questions: Vec<TypedQuestion> = question_pool
-        .choose_multiple(&mut rng, num_questions)
-        .cloned()
-        .collect();
+/// How the questions of a run are picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuizMode {
+    /// Random sample of the whole pool.
+    #[default]
+    Practice,
+    /// Spaced repetition driven by the answer history.
+    Review,
+    /// Exam simulation: countdown, no feedback until the exam is submitted.
+    Exam,
+}
+
+/// Settings that shape a single quiz run.
+#[derive(Debug, Clone)]
+pub struct QuizOptions {
+    pub num_questions: usize,
+    pub pass_mark: f64,
+    pub mode: QuizMode,
+    pub time_limit: Duration,
+    /// Restricts the pool to these domains/tags; empty means all questions.
+    pub domains: Vec<String>,
+    /// Seed of the question selection and the option order.
+    pub shuffle: Shuffle,
+    /// Offer follow-up rounds over the wrongly answered questions.
+    pub retry_wrong: bool,
+    /// Restricts the pool to the bookmarked questions, see `helper::marks`.
+    pub bookmarked: bool,
+}
+
+impl QuizOptions {
+    pub fn new(config: &AppConfig, num_questions: Option<usize>) -> Self {
+        QuizOptions {
+            num_questions: num_questions.unwrap_or(125),
+            pass_mark: config.pass_mark,
+            mode: if config.exam {
+                QuizMode::Exam
+            } else if config.review {
+                QuizMode::Review
+            } else {
+                QuizMode::Practice
+            },
+            time_limit: Duration::from_secs(config.time_limit * 60),
+            domains: config.domains.clone(),
+            shuffle: Shuffle {
+                seed: config.seed.unwrap_or_else(rand::random),
+                enabled: config.shuffle_options,
+                pin: config.pin_options,
+            },
+            retry_wrong: config.retry_wrong,
+            bookmarked: config.bookmarked,
+        }
+    }
+}
+
+impl Default for QuizOptions {
+    fn default() -> Self {
+        QuizOptions {
+            num_questions: 125,
+            pass_mark: DEFAULT_PASS_MARK,
+            mode: QuizMode::Practice,
+            time_limit: EXAM_TIME_LIMIT,
+            domains: vec![],
+            shuffle: Shuffle::off(),
+            retry_wrong: false,
+            bookmarked: false,
+        }
+    }
+}
+
+/// What happened to one question during the run.
+#[derive(Debug, Clone)]
+pub struct QuestionResult {
+    pub number: usize,
+    pub question_id: String,
+    pub question: String,
+    pub correct_answer: String,
+    pub chosen: Option<String>,
+    pub outcome: Outcome,
+    pub hint_used: bool,
+    pub tags: Vec<String>,
+    pub difficulty: Option<Difficulty>,
+    pub answered_at: SystemTime,
+    pub elapsed: Duration,
+}
+
+/// Tally of a finished (or aborted) quiz run.
+#[derive(Debug, Clone, Default)]
+pub struct QuizReport {
+    pub results: Vec<QuestionResult>,
+    pub elapsed: Duration,
+    pub pass_mark: f64,
+}
+
+impl QuizReport {
+    pub fn new(pass_mark: f64) -> Self {
+        QuizReport {
+            pass_mark,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, number: usize, question: &Question, answer: Answer, elapsed: Duration) -> &QuestionResult {
+        self.results.push(QuestionResult {
+            number,
+            question_id: question.id.clone(),
+            question: question.question.clone(),
+            correct_answer: question.correct_answers().join("; "),
+            chosen: answer.chosen,
+            outcome: answer.outcome,
+            hint_used: answer.hint_used,
+            tags: domains::effective_tags(question),
+            difficulty: question.difficulty,
+            answered_at: SystemTime::now(),
+            elapsed,
+        });
+        self.results.last().unwrap()
+    }
+
+    fn count(&self, outcome: Outcome) -> usize {
+        self.results.iter().filter(|r| r.outcome == outcome).count()
+    }
+
+    pub fn correct(&self) -> usize {
+        self.count(Outcome::Correct)
+    }
+
+    pub fn wrong(&self) -> usize {
+        self.count(Outcome::Wrong)
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(Outcome::Skipped)
+    }
+
+    pub fn total(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Share of correct answers over all asked questions; skipped ones count as not correct.
+    pub fn percentage(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+        self.correct() as f64 * 100.0 / self.total() as f64
+    }
+
+    pub fn passed(&self) -> bool {
+        self.total() > 0 && self.percentage() >= self.pass_mark
+    }
+
+    pub fn average_time(&self) -> Duration {
+        if self.total() == 0 {
+            return Duration::ZERO;
+        }
+        self.results.iter().map(|r| r.elapsed).sum::<Duration>() / self.total() as u32
+    }
+
+    pub fn slowest(&self) -> Option<&QuestionResult> {
+        self.results.iter().max_by_key(|r| r.elapsed)
+    }
 
-    let mut question_number = 1;
-    while !selected_questions.is_empty() {
-        let typed_question = selected_questions.pop().unwrap();
-        questions::ask_question(question_number, &typed_question);
-        question_number += 1;
+    /// Accuracy of this run per domain and per difficulty.
+    pub fn breakdowns(&self) -> (Breakdown, Breakdown) {
+        let mut by_domain = Breakdown::default();
+        let mut by_difficulty = Breakdown::default();
+        for result in &self.results {
+            let correct = result.outcome == Outcome::Correct;
+            if result.tags.is_empty() {
+                by_domain.add(stats::UNTAGGED, correct);
+            }
+            for tag in &result.tags {
+                by_domain.add(tag, correct);
+            }
+            by_difficulty.add(stats::difficulty_label(result.difficulty), correct);
+        }
+        (by_domain, by_difficulty)
     }
 
+    pub fn print(&self) {
+        println!("\n{}", "Quiz results".bold());
+        println!("----------------------------------------------------------------------------");
+        println!("Questions: {}", self.total());
+        println!("Correct:   {}", self.correct().to_string().green());
+        println!("Wrong:     {}", self.wrong().to_string().red());
+        println!("Skipped:   {}", self.skipped().to_string().yellow());
+        println!("Score:     {:.1}% (pass mark {:.1}%)", self.percentage(), self.pass_mark);
+        println!("Time:      {} total, {} per question", format_duration(self.elapsed), format_duration(self.average_time()));
+        if let Some(slowest) = self.slowest() {
+            println!("Slowest:   question {} ({})", slowest.number, format_duration(slowest.elapsed));
+        }
+        let (by_domain, by_difficulty) = self.breakdowns();
+        if !by_domain.is_empty() {
+            by_domain.print("Domain", self.pass_mark);
+        }
+        if by_difficulty.rows.keys().any(|label| label != stats::UNRATED) {
+            by_difficulty.print("Difficulty", self.pass_mark);
+        }
+        if self.passed() {
+            println!("\n{}", "PASS - you are ready to book the exam.".green().bold());
+        } else {
+            println!("\n{}", "FAIL - keep practicing before booking the exam.".red().bold());
+        }
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+pub fn select_questions(question_pool: &[TypedQuestion], options: &QuizOptions) -> Vec<TypedQuestion> {
+    let filtered: Vec<TypedQuestion>;
+    let question_pool = if options.domains.is_empty() {
+        question_pool
+    } else {
+        filtered = question_pool
+            .iter()
+            .filter(|q| domains::matches_domains(&q.question, &options.domains))
+            .cloned()
+            .collect();
+        &filtered
+    };
+    let bookmarked: Vec<TypedQuestion>;
+    let question_pool = if options.bookmarked {
+        let marks = marks::load_marks().unwrap_or_else(|e| {
+            eprintln!("Could not load the bookmarks: {}", e);
+            Default::default()
+        });
+        bookmarked = question_pool.iter().filter(|q| marks.is_bookmarked(&q.question.id)).cloned().collect();
+        &bookmarked
+    } else {
+        question_pool
+    };
+    match options.mode {
+        QuizMode::Practice | QuizMode::Exam => {
+            // the pool comes in hash map order, sort it so the seed alone decides the sample
+            let mut candidates: Vec<&TypedQuestion> = question_pool.iter().collect();
+            candidates.sort_by(|a, b| a.question.id.cmp(&b.question.id));
+            let mut rng = StdRng::seed_from_u64(options.shuffle.seed);
+            candidates
+                .choose_multiple(&mut rng, options.num_questions)
+                .map(|q| (*q).clone())
+                .collect()
+        }
+        QuizMode::Review => {
+            let log = history::load_answer_log().unwrap_or_else(|e| {
+                eprintln!("Could not load answer history, every question counts as new: {}", e);
+                Default::default()
+            });
+            let now = history::unix_seconds(SystemTime::now());
+            review::schedule(question_pool, &log, now, options.num_questions)
+        }
+    }
+}
+
+/// Runs a new quiz. Returns `None` when the user stopped it; the session is saved then.
+pub fn run_quiz(question_pool: Vec<TypedQuestion>, options: &QuizOptions) -> Option<QuizReport> {
+    let selected_questions = select_questions(&question_pool, options);
+    if selected_questions.is_empty() {
+        if options.bookmarked {
+            println!("{}", "No bookmarked questions - press B at the question prompt to bookmark one.".yellow());
+        } else if options.mode == QuizMode::Review {
+            println!("{}", "Nothing to review right now - every question is scheduled for later.".yellow());
+        } else {
+            println!("{}", format!("No questions found for domains: {}", options.domains.join(", ")).yellow());
+        }
+        return Some(QuizReport::new(options.pass_mark));
+    }
+    let presentations: Vec<Presentation> =
+        selected_questions.iter().map(|q| Presentation::new(&q.question, &options.shuffle)).collect();
+    let saved = SavedSession::new(&selected_questions, &presentations, options);
+    play(selected_questions, presentations, saved, options)
+}
+
+/// Continues a saved quiz with the same questions, option order, answers and clock.
+pub fn resume_quiz(question_pool: Vec<TypedQuestion>, saved: SavedSession, shuffle: &Shuffle) -> anyhow::Result<Option<QuizReport>> {
+    let (selected_questions, presentations) = saved.restore(&question_pool)?;
+    let options = saved.options(shuffle);
+    Ok(play(selected_questions, presentations, saved, &options))
+}
+
+fn play(selected_questions: Vec<TypedQuestion>, presentations: Vec<Presentation>, saved: SavedSession, options: &QuizOptions) -> Option<QuizReport> {
+    let report = match options.mode {
+        QuizMode::Exam => {
+            let mut exam_session = exam::ExamSession::with_presentations(selected_questions.clone(), presentations);
+            let report = exam::run_exam(&mut exam_session, saved, options.pass_mark, options.time_limit)?;
+            store_report(&report);
+            report
+        }
+        QuizMode::Practice | QuizMode::Review => run_practice(&selected_questions, &presentations, saved, options)?,
+    };
+    session::clear();
+
     println!("Congratulations! You have completed the quiz.");
+    report.print();
+    if options.mode != QuizMode::Review {
+        println!("Repeat this quiz with --seed {}", options.shuffle.seed);
+    }
+    if options.retry_wrong {
+        run_retry_rounds(&selected_questions, &report, &options.shuffle);
+    }
     println!("Press Enter to exit...");
     io::stdin().read_line(&mut String::new()).unwrap();
-}
\ No newline at end of file
+    Some(report)
+}
+
+/// Asks the questions one by one with immediate feedback, storing each answer as it is given.
+/// Answers already in `saved` are taken over; `None` when the user stops before the end.
+fn run_practice(selected_questions: &[TypedQuestion], presentations: &[Presentation], mut saved: SavedSession, options: &QuizOptions) -> Option<QuizReport> {
+    let mut report = QuizReport::new(options.pass_mark);
+    for (index, (typed_question, answer)) in selected_questions.iter().zip(&saved.answers).enumerate() {
+        report.record(index + 1, &typed_question.question, answer.answer(), Duration::from_millis(answer.elapsed_ms));
+    }
+    let mut history = History::resume(saved.history_session, &report).map_err(|e| eprintln!("History disabled: {}", e)).ok();
+    saved.history_session = history.as_ref().and_then(History::saved_id);
+    let earlier = saved.elapsed();
+    let quiz_start = Instant::now();
+    for (index, typed_question) in selected_questions.iter().enumerate().skip(report.total()) {
+        let question_start = Instant::now();
+        let Some(answer) = questions::ask_question(index + 1, &presentations[index]) else {
+            saved.capture_answers(&report.results, earlier + quiz_start.elapsed());
+            session::checkpoint(&mut saved);
+            println!("{}", "Quiz saved, continue it with `quiz --resume`.".yellow());
+            return None;
+        };
+        let result = report.record(index + 1, &typed_question.question, answer, question_start.elapsed());
+        if let Some(history) = history.as_mut() {
+            if let Err(e) = history.record_answer(result) {
+                eprintln!("Failed to store answer: {}", e);
+            }
+        }
+        // after every answer, so an interrupt loses nothing that was answered
+        saved.capture_answers(&report.results, earlier + quiz_start.elapsed());
+        session::checkpoint(&mut saved);
+    }
+    report.elapsed = earlier + quiz_start.elapsed();
+    if let Some(history) = history.as_mut() {
+        if let Err(e) = history.finish(&report) {
+            eprintln!("Failed to store quiz session: {}", e);
+        }
+    }
+    Some(report)
+}
+
+/// Follow-up rounds that re-ask the wrongly answered questions until each one is answered
+/// correctly. Retries are drill only: they are not graded and not stored in the history.
+#[derive(Debug, Clone, Default)]
+pub struct RetryDrill {
+    pub pending: Vec<TypedQuestion>,
+    /// Solved questions with the number of retry rounds they needed.
+    pub solved: Vec<(TypedQuestion, usize)>,
+    pub round: usize,
+}
+
+impl RetryDrill {
+    /// Starts with the questions of `report` that were answered wrong.
+    pub fn new(selected_questions: &[TypedQuestion], report: &QuizReport) -> Self {
+        let pending = report
+            .results
+            .iter()
+            .filter(|r| r.outcome == Outcome::Wrong)
+            .filter_map(|r| selected_questions.get(r.number - 1).cloned())
+            .collect();
+        RetryDrill { pending, ..Default::default() }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Takes the pending questions in a new order for the next round.
+    pub fn next_round(&mut self, rng: &mut StdRng) -> Vec<TypedQuestion> {
+        self.round += 1;
+        let mut questions = std::mem::take(&mut self.pending);
+        questions.shuffle(rng);
+        questions
+    }
+
+    /// Solved when answered correctly, otherwise it comes again next round.
+    pub fn record(&mut self, question: TypedQuestion, outcome: Outcome) {
+        if outcome == Outcome::Correct {
+            self.solved.push((question, self.round));
+        } else {
+            self.pending.push(question);
+        }
+    }
+
+    pub fn print_summary(&self) {
+        println!("\n{}", "Retry summary".bold());
+        for (typed_question, rounds) in &self.solved {
+            let label = if *rounds == 1 { "1 round".to_string() } else { format!("{} rounds", rounds) };
+            println!("  {:>9}  {}", label.green(), typed_question.question.question);
+        }
+        for typed_question in &self.pending {
+            println!("  {:>9}  {}", "open".red(), typed_question.question.question);
+        }
+        println!("{} of {} solved after {} rounds.", self.solved.len(), self.solved.len() + self.pending.len(), self.round);
+    }
+}
+
+fn run_retry_rounds(selected_questions: &[TypedQuestion], report: &QuizReport, shuffle: &Shuffle) {
+    let mut drill = RetryDrill::new(selected_questions, report);
+    if drill.is_done() {
+        return;
+    }
+    let mut rng = StdRng::seed_from_u64(shuffle.seed.wrapping_add(1));
+    while !drill.is_done() {
+        print!("\n{} questions answered wrong. Start retry round {}? (yes/no): ", drill.pending.len(), drill.round + 1);
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).unwrap();
+        if !matches!(input.trim().to_lowercase().as_str(), "yes" | "y") {
+            break;
+        }
+        let round = drill.round + 1;
+        // every round shows the options in a new order as well
+        let round_shuffle = Shuffle { seed: shuffle.seed.wrapping_add(round as u64), ..*shuffle };
+        let questions = drill.next_round(&mut rng);
+        let count = questions.len();
+        let mut quit = false;
+        for (index, typed_question) in questions.into_iter().enumerate() {
+            // after X the rest of the round stays open
+            if quit {
+                drill.record(typed_question, Outcome::Skipped);
+                continue;
+            }
+            let presentation = Presentation::new(&typed_question.question, &round_shuffle);
+            match questions::ask_question(index + 1, &presentation) {
+                Some(answer) => drill.record(typed_question, answer.outcome),
+                None => {
+                    quit = true;
+                    drill.record(typed_question, Outcome::Skipped);
+                }
+            }
+        }
+        if quit {
+            break;
+        }
+        println!("Round {}: {} of {} correct.", round, count - drill.pending.len(), count);
+    }
+    drill.print_summary();
+}
+
+/// Stores a complete report in one go, for modes that only grade at the end.
+fn store_report(report: &QuizReport) {
+    let stored = History::start(report).and_then(|mut history| {
+        for result in &report.results {
+            history.record_answer(result)?;
+        }
+        history.finish(report)
+    });
+    if let Err(e) = stored {
+        eprintln!("Failed to store quiz session: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(outcome: Outcome) -> Answer {
+        Answer { outcome, chosen: None, hint_used: false }
+    }
+
+    #[test]
+    fn test_report_scores_against_pass_mark() {
+        let mut report = QuizReport::new(70.0);
+        let question = Question::new();
+        for outcome in [Outcome::Correct, Outcome::Correct, Outcome::Correct, Outcome::Wrong] {
+            report.record(report.total() + 1, &question, answer(outcome), Duration::from_secs(10));
+        }
+        assert_eq!(report.percentage(), 75.0);
+        assert!(report.passed());
+
+        report.record(5, &question, answer(Outcome::Skipped), Duration::from_secs(30));
+        assert_eq!(report.skipped(), 1);
+        assert_eq!(report.percentage(), 60.0);
+        assert!(!report.passed());
+        assert_eq!(report.slowest().unwrap().number, 5);
+        assert_eq!(report.average_time(), Duration::from_secs(14));
+    }
+
+    #[test]
+    fn test_retry_drill_counts_rounds_per_question() {
+        let typed = |text: &str| TypedQuestion { question: Question { question: text.to_string(), ..Question::new() }, ..TypedQuestion::new() };
+        let selected = vec![typed("right"), typed("wrong once"), typed("wrong twice")];
+        let mut report = QuizReport::new(70.0);
+        for (question, outcome) in selected.iter().zip([Outcome::Correct, Outcome::Wrong, Outcome::Wrong]) {
+            report.record(report.total() + 1, &question.question, answer(outcome), Duration::ZERO);
+        }
+        let mut drill = RetryDrill::new(&selected, &report);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(drill.pending.len(), 2);
+        for question in drill.next_round(&mut rng) {
+            let outcome = if question.question.question == "wrong once" { Outcome::Correct } else { Outcome::Skipped };
+            drill.record(question, outcome);
+        }
+        for question in drill.next_round(&mut rng) {
+            drill.record(question, Outcome::Correct);
+        }
+        assert!(drill.is_done());
+        let solved: Vec<(&str, usize)> = drill.solved.iter().map(|(q, r)| (q.question.question.as_str(), *r)).collect();
+        assert_eq!(solved, vec![("wrong once", 1), ("wrong twice", 2)]);
+    }
+
+    #[test]
+    fn test_empty_report_does_not_pass() {
+        let report = QuizReport::new(0.0);
+        assert_eq!(report.percentage(), 0.0);
+        assert!(!report.passed());
+    }
+}
@@ -0,0 +1,590 @@
+//helper/repository.rs
+// Normalized question storage in questions.db (use_db feature).
+//
+// The schema is versioned with `PRAGMA user_version`; `migrate` applies every migration
+// newer than the stored version, so old databases (blob `questions` table, `answers`
+// history) are upgraded in place.
+#![cfg(feature = "use_db")]
+
+use std::collections::{HashMap, HashSet};
+use std::time::SystemTime;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::questions::{Difficulty, Question};
+use super::history::unix_seconds;
+
+type Migration = fn(&Connection) -> rusqlite::Result<()>;
+
+/// Schema migrations in order; the database stores how many of them ran.
+const MIGRATIONS: &[Migration] = &[
+    migrate_history_tables,
+    migrate_question_tables,
+    migrate_answers_to_attempts,
+    migrate_search_index,
+    migrate_explanations,
+    migrate_distractors_and_references,
+];
+
+pub fn schema_version(conn: &Connection) -> anyhow::Result<usize> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))? as usize)
+}
+
+/// Brings the schema up to date. Each migration runs in its own transaction together with
+/// the version bump, so an interrupted upgrade is simply retried on the next start.
+pub fn migrate(conn: &Connection) -> anyhow::Result<()> {
+    let current = schema_version(conn)?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.unchecked_transaction()?;
+        migration(&tx)?;
+        tx.execute_batch(&format!("PRAGMA user_version = {};", index + 1))?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+fn table_has_column(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    conn.prepare("SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2")?.exists([table, column])
+}
+
+/// 1: quiz sessions and answers, including the question_id column added with stable ids.
+fn migrate_history_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id INTEGER PRIMARY KEY,
+            started_at INTEGER NOT NULL,
+            finished_at INTEGER,
+            pass_mark REAL NOT NULL,
+            total INTEGER NOT NULL DEFAULT 0,
+            correct INTEGER NOT NULL DEFAULT 0,
+            wrong INTEGER NOT NULL DEFAULT 0,
+            skipped INTEGER NOT NULL DEFAULT 0,
+            score REAL,
+            elapsed_ms INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS answers (
+            id INTEGER PRIMARY KEY,
+            session_id INTEGER NOT NULL REFERENCES sessions(id),
+            question_id TEXT,
+            question TEXT NOT NULL,
+            chosen TEXT,
+            correct_answer TEXT NOT NULL,
+            outcome TEXT NOT NULL,
+            hint_used INTEGER NOT NULL,
+            answered_at INTEGER NOT NULL,
+            elapsed_ms INTEGER NOT NULL
+        );",
+    )?;
+    // Databases created before questions had ids lack the question_id column.
+    if !table_has_column(conn, "answers", "question_id")? {
+        conn.execute_batch("ALTER TABLE answers ADD COLUMN question_id TEXT;")?;
+    }
+    Ok(())
+}
+
+/// 2: questions with their options, tags, hints and the bank they came from.
+fn migrate_question_tables(conn: &Connection) -> rusqlite::Result<()> {
+    // The old `questions` table only held a JSON copy of the banks; `db sync` refills it.
+    if table_has_column(conn, "questions", "data")? {
+        conn.execute_batch("DROP TABLE questions;")?;
+    }
+    conn.execute_batch(
+        "CREATE TABLE questions (
+            id TEXT PRIMARY KEY,
+            position INTEGER NOT NULL,
+            question TEXT NOT NULL,
+            answer TEXT NOT NULL,
+            multi_answer INTEGER NOT NULL DEFAULT 0,
+            difficulty TEXT,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE options (
+            question_id TEXT NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            correct INTEGER NOT NULL,
+            PRIMARY KEY (question_id, position)
+        );
+        CREATE TABLE tags (
+            question_id TEXT NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (question_id, tag)
+        );
+        CREATE INDEX tags_tag ON tags(tag);
+        CREATE TABLE hints (
+            question_id TEXT NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            PRIMARY KEY (question_id, position)
+        );
+        CREATE TABLE sources (
+            question_id TEXT PRIMARY KEY REFERENCES questions(id) ON DELETE CASCADE,
+            bank TEXT NOT NULL
+        );",
+    )
+}
+
+/// 3: answers become attempts, linked to questions by id.
+fn migrate_answers_to_attempts(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS answers_question;
+        DROP INDEX IF EXISTS answers_question_id;
+        ALTER TABLE answers RENAME TO attempts;
+        CREATE INDEX attempts_question_id ON attempts(question_id);
+        CREATE INDEX attempts_session_id ON attempts(session_id);",
+    )
+}
+
+/// 4: FTS5 index over question text, options and hint, kept up to date by `write_question`.
+fn migrate_search_index(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE questions_fts USING fts5(id UNINDEXED, question, options, hint);
+        INSERT INTO questions_fts (id, question, options, hint)
+            SELECT q.id, q.question,
+                   COALESCE((SELECT group_concat(text, char(10)) FROM options o WHERE o.question_id = q.id), ''),
+                   COALESCE((SELECT text FROM hints h WHERE h.question_id = q.id AND h.position = 0), '')
+            FROM questions q;",
+    )
+}
+
+/// 5: explanation shown after a question was answered.
+fn migrate_explanations(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("ALTER TABLE questions ADD COLUMN explanation TEXT;")
+}
+
+/// 6: why a wrong option is wrong, and references to the CEH modules.
+fn migrate_distractors_and_references(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE options ADD COLUMN why_wrong TEXT;
+        CREATE TABLE question_references (
+            question_id TEXT NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            PRIMARY KEY (question_id, position)
+        );",
+    )
+}
+
+fn difficulty_to_sql(difficulty: Option<Difficulty>) -> Option<String> {
+    difficulty.and_then(|d| serde_json::to_value(d).ok()).and_then(|v| v.as_str().map(str::to_string))
+}
+
+fn difficulty_from_sql(value: Option<String>) -> Option<Difficulty> {
+    value.and_then(|v| serde_json::from_value(serde_json::Value::String(v)).ok())
+}
+
+/// What `QuestionRepository::sync` did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    /// Stored questions missing from the input that were kept because pruning was off.
+    pub kept: usize,
+}
+
+/// CRUD access to the normalized question tables.
+pub struct QuestionRepository {
+    conn: Connection,
+}
+
+impl QuestionRepository {
+    /// Opens questions.db next to the primary bank and migrates it.
+    pub fn open() -> anyhow::Result<Self> {
+        Self::with_connection(super::history::open_db()?)
+    }
+
+    pub fn with_connection(conn: Connection) -> anyhow::Result<Self> {
+        migrate(&conn)?;
+        Ok(QuestionRepository { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn count(&self) -> anyhow::Result<usize> {
+        Ok(self.conn.query_row("SELECT COUNT(*) FROM questions", [], |row| row.get::<_, i64>(0))? as usize)
+    }
+
+    pub fn exists(&self, id: &str) -> anyhow::Result<bool> {
+        Ok(self.conn.prepare("SELECT 1 FROM questions WHERE id = ?1")?.exists([id])?)
+    }
+
+    /// Adds a new question at the end; fails if the id is taken.
+    pub fn insert(&self, question: &Question) -> anyhow::Result<()> {
+        if question.id.is_empty() {
+            anyhow::bail!("question has no id: {}", question.question);
+        }
+        if self.exists(&question.id)? {
+            anyhow::bail!("question {} already exists", question.id);
+        }
+        let position: i64 = self.conn.query_row("SELECT COALESCE(MAX(position), 0) + 1 FROM questions", [], |row| row.get(0))?;
+        let tx = self.conn.unchecked_transaction()?;
+        write_question(&tx, question, position)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replaces a stored question, keeping its position. Returns false if there is none with that id.
+    pub fn update(&self, question: &Question) -> anyhow::Result<bool> {
+        let position: Option<i64> = self.conn
+            .query_row("SELECT position FROM questions WHERE id = ?1", [&question.id], |row| row.get(0))
+            .optional()?;
+        let Some(position) = position else {
+            return Ok(false);
+        };
+        let tx = self.conn.unchecked_transaction()?;
+        delete_question(&tx, &question.id)?;
+        write_question(&tx, question, position)?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Inserts or replaces a question.
+    pub fn save(&self, question: &Question) -> anyhow::Result<()> {
+        if !self.update(question)? {
+            self.insert(question)?;
+        }
+        Ok(())
+    }
+
+    /// Makes the stored questions match `questions` in one transaction: new ids are inserted,
+    /// changed ones replaced, identical ones left alone. With `prune` questions missing from
+    /// `questions` are deleted. Running it twice with the same input changes nothing.
+    pub fn sync(&self, questions: &[Question], prune: bool) -> anyhow::Result<SyncReport> {
+        let stored: HashMap<String, Question> = self.list()?.into_iter().map(|q| (q.id.clone(), q)).collect();
+        let mut positions: HashMap<String, i64> = HashMap::new();
+        {
+            let mut stmt = self.conn.prepare("SELECT id, position FROM questions")?;
+            for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))? {
+                let (id, position) = row?;
+                positions.insert(id, position);
+            }
+        }
+        let mut next = positions.values().max().copied().unwrap_or(0) + 1;
+        let mut report = SyncReport::default();
+        let mut seen: HashSet<&str> = HashSet::new();
+        let tx = self.conn.unchecked_transaction()?;
+        for question in questions {
+            if question.id.is_empty() {
+                anyhow::bail!("question has no id: {}", question.question);
+            }
+            if !seen.insert(&question.id) {
+                continue;
+            }
+            match stored.get(&question.id) {
+                Some(existing) if *existing == stored_form(question) => report.unchanged += 1,
+                Some(_) => {
+                    delete_question(&tx, &question.id)?;
+                    write_question(&tx, question, positions[&question.id])?;
+                    report.updated += 1;
+                }
+                None => {
+                    write_question(&tx, question, next)?;
+                    next += 1;
+                    report.inserted += 1;
+                }
+            }
+        }
+        for id in stored.keys().filter(|id| !seen.contains(id.as_str())) {
+            if prune {
+                delete_question(&tx, id)?;
+                report.removed += 1;
+            } else {
+                report.kept += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(report)
+    }
+
+    /// Removes a question; its recorded attempts are kept. Returns false if it did not exist.
+    pub fn delete(&self, id: &str) -> anyhow::Result<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+        let deleted = delete_question(&tx, id)?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    pub fn get(&self, id: &str) -> anyhow::Result<Option<Question>> {
+        Ok(self.query("WHERE q.id = ?1", params![id])?.into_iter().next())
+    }
+
+    /// All questions in bank order.
+    pub fn list(&self) -> anyhow::Result<Vec<Question>> {
+        self.query("", [])
+    }
+
+    /// Questions `db sync` read from the bank file named `bank`, see `bank::source_name`.
+    pub fn in_bank(&self, bank: &str) -> anyhow::Result<Vec<Question>> {
+        self.query("WHERE s.bank = ?1", params![bank])
+    }
+
+    pub fn with_tag(&self, tag: &str) -> anyhow::Result<Vec<Question>> {
+        self.query("WHERE q.id IN (SELECT question_id FROM tags WHERE tag = ?1)", params![tag])
+    }
+
+    /// Questions answered wrong at least `times` times, optionally only those tagged `tag`.
+    pub fn wrong_at_least(&self, times: usize, tag: Option<&str>) -> anyhow::Result<Vec<Question>> {
+        self.query(
+            "WHERE (SELECT COUNT(*) FROM attempts a WHERE a.question_id = q.id AND a.outcome = 'Wrong') >= ?1
+               AND (?2 IS NULL OR q.id IN (SELECT question_id FROM tags WHERE tag = ?2))",
+            params![times as i64, tag],
+        )
+    }
+
+    /// Full-text search with FTS5; `query` is an FTS5 match expression (see `search::fts_query`).
+    /// Returns the questions with their bm25 rank, best first; question text weighs most.
+    pub fn search(&self, query: &str, limit: usize) -> anyhow::Result<Vec<(Question, f64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, -bm25(questions_fts, 0.0, 3.0, 1.0, 1.0) AS score FROM questions_fts
+             WHERE questions_fts MATCH ?1 ORDER BY score DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![query, limit as i64], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+        let mut hits = vec![];
+        for row in rows {
+            let (id, score) = row?;
+            if let Some(question) = self.get(&id)? {
+                hits.push((question, score));
+            }
+        }
+        Ok(hits)
+    }
+
+    fn query(&self, filter: &str, args: impl rusqlite::Params) -> anyhow::Result<Vec<Question>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT q.id, q.question, q.answer, q.multi_answer, q.difficulty, s.bank, q.explanation
+             FROM questions q LEFT JOIN sources s ON s.question_id = q.id {} ORDER BY q.position",
+            filter
+        ))?;
+        let rows = stmt.query_map(args, |row| {
+            let multi_answer: bool = row.get(3)?;
+            Ok((
+                Question {
+                    id: row.get(0)?,
+                    question: row.get(1)?,
+                    answer: row.get(2)?,
+                    difficulty: difficulty_from_sql(row.get(4)?),
+                    source: row.get(5)?,
+                    explanation: row.get(6)?,
+                    options: vec![],
+                    ..Question::new()
+                },
+                multi_answer,
+            ))
+        })?;
+        let mut questions = vec![];
+        for row in rows {
+            let (mut question, multi_answer) = row?;
+            self.load_children(&mut question, multi_answer)?;
+            questions.push(question);
+        }
+        Ok(questions)
+    }
+
+    fn load_children(&self, question: &mut Question, multi_answer: bool) -> anyhow::Result<()> {
+        let mut options = self.conn.prepare_cached("SELECT text, correct, why_wrong FROM options WHERE question_id = ?1 ORDER BY position")?;
+        let rows = options.query_map([&question.id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?, row.get::<_, Option<String>>(2)?)))?;
+        for option in rows {
+            let (text, correct, why_wrong) = option?;
+            if multi_answer && correct {
+                question.answers.push(text.clone());
+            }
+            if let Some(why_wrong) = why_wrong {
+                question.distractors.insert(text.clone(), why_wrong);
+            }
+            question.options.push(text);
+        }
+        let mut tags = self.conn.prepare_cached("SELECT tag FROM tags WHERE question_id = ?1 ORDER BY position")?;
+        question.tags = tags.query_map([&question.id], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        let mut hints = self.conn.prepare_cached("SELECT text FROM hints WHERE question_id = ?1 ORDER BY position LIMIT 1")?;
+        question.hint = hints.query_row([&question.id], |row| row.get(0)).optional()?;
+        let mut references = self.conn.prepare_cached("SELECT text FROM question_references WHERE question_id = ?1 ORDER BY position")?;
+        question.references = references.query_map([&question.id], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        Ok(())
+    }
+}
+
+/// `question` as `write_question` and `load_children` round-trip it: repeated tags once
+/// (`INSERT OR IGNORE`), correct answers and distractor notes only for existing options.
+fn stored_form(question: &Question) -> Question {
+    let mut stored = question.clone();
+    let mut seen = HashSet::new();
+    stored.tags.retain(|tag| seen.insert(tag.clone()));
+    if question.is_multi_answer() {
+        let correct = question.correct_answers();
+        stored.answers = question.options.iter().filter(|o| correct.contains(&o.as_str())).cloned().collect();
+    }
+    stored.distractors.retain(|option, _| question.options.contains(option));
+    stored
+}
+
+fn write_question(conn: &Connection, question: &Question, position: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO questions (id, position, question, answer, multi_answer, difficulty, updated_at, explanation)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            question.id,
+            position,
+            question.question,
+            question.answer,
+            question.is_multi_answer(),
+            difficulty_to_sql(question.difficulty),
+            unix_seconds(SystemTime::now()),
+            question.explanation,
+        ],
+    )?;
+    let correct = question.correct_answers();
+    for (index, option) in question.options.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO options (question_id, position, text, correct, why_wrong) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![question.id, index as i64, option, correct.contains(&option.as_str()), question.distractors.get(option)],
+        )?;
+    }
+    for (index, tag) in question.tags.iter().enumerate() {
+        conn.execute(
+            "INSERT OR IGNORE INTO tags (question_id, position, tag) VALUES (?1, ?2, ?3)",
+            params![question.id, index as i64, tag],
+        )?;
+    }
+    if let Some(hint) = &question.hint {
+        conn.execute("INSERT INTO hints (question_id, position, text) VALUES (?1, 0, ?2)", params![question.id, hint])?;
+    }
+    for (index, reference) in question.references.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO question_references (question_id, position, text) VALUES (?1, ?2, ?3)",
+            params![question.id, index as i64, reference],
+        )?;
+    }
+    if let Some(bank) = &question.source {
+        conn.execute("INSERT INTO sources (question_id, bank) VALUES (?1, ?2)", params![question.id, bank])?;
+    }
+    conn.execute(
+        "INSERT INTO questions_fts (id, question, options, hint) VALUES (?1, ?2, ?3, ?4)",
+        params![question.id, question.question, question.options.join("\n"), question.hint.as_deref().unwrap_or("")],
+    )?;
+    Ok(())
+}
+
+fn delete_question(conn: &Connection, id: &str) -> rusqlite::Result<bool> {
+    for table in ["options", "tags", "hints", "question_references", "sources"] {
+        conn.execute(&format!("DELETE FROM {} WHERE question_id = ?1", table), [id])?;
+    }
+    conn.execute("DELETE FROM questions_fts WHERE id = ?1", [id])?;
+    Ok(conn.execute("DELETE FROM questions WHERE id = ?1", [id])? > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(id: &str, tags: &[&str]) -> Question {
+        Question {
+            id: id.to_string(),
+            question: format!("Question {}?", id),
+            answer: "B. yes".to_string(),
+            hint: Some("think".to_string()),
+            options: vec!["A. no".to_string(), "B. yes".to_string()],
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            difficulty: Some(Difficulty::Hard),
+            source: Some("questions.json".to_string()),
+            explanation: Some("because".to_string()),
+            distractors: [("A. no".to_string(), "it is yes".to_string())].into(),
+            references: vec!["Module 02".to_string(), "Module 03".to_string()],
+            ..Question::new()
+        }
+    }
+
+    #[test]
+    fn test_crud_roundtrip() {
+        let repo = QuestionRepository::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let mut multi = question("b", &["cryptography"]);
+        multi.answers = vec!["A. no".to_string(), "B. yes".to_string()];
+        repo.insert(&question("a", &["cloud", "iot"])).unwrap();
+        repo.insert(&multi).unwrap();
+        assert!(repo.insert(&multi).is_err());
+
+        assert_eq!(repo.list().unwrap(), vec![question("a", &["cloud", "iot"]), multi.clone()]);
+        assert_eq!(repo.with_tag("cryptography").unwrap(), vec![multi.clone()]);
+        assert_eq!(repo.in_bank("questions.json").unwrap().len(), 2);
+        assert!(repo.in_bank("team.json").unwrap().is_empty());
+
+        let mut edited = question("a", &["cloud"]);
+        edited.hint = None;
+        assert!(repo.update(&edited).unwrap());
+        assert_eq!(repo.get("a").unwrap(), Some(edited));
+        assert_eq!(repo.list().unwrap()[0].id, "a");
+
+        assert!(repo.delete("a").unwrap());
+        assert!(!repo.delete("a").unwrap());
+        assert_eq!(repo.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_migrates_legacy_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE questions (id TEXT PRIMARY KEY, data JSON NOT NULL);
+            CREATE TABLE sessions (id INTEGER PRIMARY KEY, started_at INTEGER NOT NULL, finished_at INTEGER,
+                pass_mark REAL NOT NULL, total INTEGER NOT NULL DEFAULT 0, correct INTEGER NOT NULL DEFAULT 0,
+                wrong INTEGER NOT NULL DEFAULT 0, skipped INTEGER NOT NULL DEFAULT 0, score REAL, elapsed_ms INTEGER);
+            CREATE TABLE answers (id INTEGER PRIMARY KEY, session_id INTEGER NOT NULL, question TEXT NOT NULL,
+                chosen TEXT, correct_answer TEXT NOT NULL, outcome TEXT NOT NULL, hint_used INTEGER NOT NULL,
+                answered_at INTEGER NOT NULL, elapsed_ms INTEGER NOT NULL);
+            INSERT INTO sessions (id, started_at, pass_mark) VALUES (1, 0, 70);
+            INSERT INTO answers (session_id, question, correct_answer, outcome, hint_used, answered_at, elapsed_ms)
+                VALUES (1, 'Question c?', 'B. yes', 'Wrong', 0, 1, 10), (1, 'Question c?', 'B. yes', 'Wrong', 0, 2, 10);",
+        ).unwrap();
+        let repo = QuestionRepository::with_connection(conn).unwrap();
+        assert_eq!(schema_version(repo.connection()).unwrap(), MIGRATIONS.len());
+
+        repo.insert(&question("c", &["cryptography"])).unwrap();
+        repo.insert(&question("d", &["cryptography"])).unwrap();
+        repo.connection().execute("UPDATE attempts SET question_id = 'c'", []).unwrap();
+        let wrong: Vec<String> = repo.wrong_at_least(2, Some("cryptography")).unwrap().into_iter().map(|q| q.id).collect();
+        assert_eq!(wrong, vec!["c"]);
+        assert!(repo.wrong_at_least(2, Some("cloud")).unwrap().is_empty());
+
+        // running the migrations again is a no-op
+        migrate(repo.connection()).unwrap();
+    }
+
+    #[test]
+    fn test_full_text_search() {
+        let repo = QuestionRepository::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let mut dnssec = question("dnssec", &[]);
+        dnssec.question = "What does DNSSEC add to DNS responses?".to_string();
+        let mut zone = question("zone", &[]);
+        zone.hint = Some("Related to DNS zone transfers".to_string());
+        repo.sync(&[zone, dnssec], false).unwrap();
+
+        let ids = |query: &str| -> Vec<String> {
+            repo.search(query, 10).unwrap().into_iter().map(|(q, _)| q.id).collect()
+        };
+        assert_eq!(ids("\"dns\"*"), vec!["dnssec", "zone"]);
+        assert_eq!(ids("\"zone transfers\""), vec!["zone"]);
+        repo.delete("zone").unwrap();
+        assert_eq!(ids("\"zone\"*"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_sync_is_idempotent() {
+        let repo = QuestionRepository::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        // a repeated tag is stored once and must not count as a change
+        let mut bank = vec![question("a", &[]), question("b", &["cloud"]), question("c", &["iot", "iot"])];
+        let first = repo.sync(&bank, false).unwrap();
+        assert_eq!((first.inserted, first.unchanged), (3, 0));
+        let again = repo.sync(&bank, false).unwrap();
+        assert_eq!(again, SyncReport { unchanged: 3, ..Default::default() });
+
+        bank[1].answer = "A. no".to_string();
+        bank.remove(2);
+        bank.push(question("d", &[]));
+        let kept = repo.sync(&bank, false).unwrap();
+        assert_eq!(kept, SyncReport { inserted: 1, updated: 1, unchanged: 1, kept: 1, ..Default::default() });
+        let pruned = repo.sync(&bank, true).unwrap();
+        assert_eq!(pruned, SyncReport { unchanged: 3, removed: 1, ..Default::default() });
+        assert_eq!(repo.list().unwrap(), bank);
+    }
+}
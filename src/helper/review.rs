@@ -0,0 +1,112 @@
+//helper/review.rs
+// Leitner style spaced repetition: every correct answer moves a question one box up,
+// a wrong or skipped answer drops it back to box 0. Higher boxes come back later.
+use std::collections::HashMap;
+use rand::seq::SliceRandom;
+use crate::questions::TypedQuestion;
+use super::history::AnswerEvent;
+
+const DAY: i64 = 24 * 60 * 60;
+/// How long a question rests in each box before it is due again.
+pub const BOX_INTERVALS: [i64; 6] = [0, DAY, 3 * DAY, 7 * DAY, 14 * DAY, 30 * DAY];
+/// Questions in the last box are considered mastered.
+pub const MASTERED_BOX: usize = BOX_INTERVALS.len() - 1;
+
+/// Scheduling state of a question derived from its answer history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardState {
+    pub leitner_box: usize,
+    pub last_answered: i64,
+}
+
+impl CardState {
+    pub fn from_history(events: &[AnswerEvent]) -> Option<Self> {
+        let last = events.last()?;
+        let leitner_box = events.iter().fold(0, |current, event| {
+            if event.correct { (current + 1).min(MASTERED_BOX) } else { 0 }
+        });
+        Some(CardState { leitner_box, last_answered: last.answered_at })
+    }
+
+    pub fn due_at(&self) -> i64 {
+        self.last_answered + BOX_INTERVALS[self.leitner_box]
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        self.due_at() <= now
+    }
+}
+
+/// Picks up to `num_questions` for a review session.
+///
+/// Due questions come first (lowest box, then most overdue), followed by questions
+/// never answered before in random order. Questions that are not due yet are left out,
+/// so mastered questions fade away until their interval runs out.
+pub fn schedule(pool: &[TypedQuestion], log: &HashMap<String, Vec<AnswerEvent>>, now: i64, num_questions: usize) -> Vec<TypedQuestion> {
+    let mut due: Vec<(CardState, &TypedQuestion)> = vec![];
+    let mut unseen: Vec<&TypedQuestion> = vec![];
+    for typed_question in pool {
+        let question = &typed_question.question;
+        let events = log.get(&question.id).or_else(|| log.get(&question.question));
+        match events.and_then(|events| CardState::from_history(events)) {
+            Some(state) if state.is_due(now) => due.push((state, typed_question)),
+            Some(_) => {}
+            None => unseen.push(typed_question),
+        }
+    }
+    due.sort_by_key(|(state, _)| (state.leitner_box, state.due_at()));
+    unseen.shuffle(&mut rand::thread_rng());
+
+    due.into_iter()
+        .map(|(_, q)| q)
+        .chain(unseen)
+        .take(num_questions)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::{Question, QuestionType};
+
+    fn typed(text: &str) -> TypedQuestion {
+        let mut question = Question::new();
+        question.id = format!("id-{}", text);
+        question.question = text.to_string();
+        TypedQuestion { qtype: QuestionType::DefaultItem, question }
+    }
+
+    fn event(answered_at: i64, correct: bool) -> AnswerEvent {
+        AnswerEvent { answered_at, correct }
+    }
+
+    #[test]
+    fn test_wrong_answer_resets_box() {
+        let state = CardState::from_history(&[event(0, true), event(10, true), event(20, false)]).unwrap();
+        assert_eq!(state.leitner_box, 0);
+        let state = CardState::from_history(&[event(0, false), event(10, true), event(20, true)]).unwrap();
+        assert_eq!(state.leitner_box, 2);
+        assert_eq!(state.due_at(), 20 + 3 * DAY);
+        assert!(CardState::from_history(&[]).is_none());
+    }
+
+    #[test]
+    fn test_schedule_prefers_due_questions_and_hides_fresh_ones() {
+        let pool = vec![typed("wrong"), typed("known"), typed("new"), typed("old")];
+        let mut log = HashMap::new();
+        log.insert("id-wrong".to_string(), vec![event(100, false)]);
+        log.insert("id-known".to_string(), vec![event(100, true), event(200, true)]);
+        // answers recorded before ids existed are keyed by the question text
+        log.insert("old".to_string(), vec![event(0, true)]);
+        let now = 2 * DAY;
+
+        let selected: Vec<String> = schedule(&pool, &log, now, 10)
+            .into_iter()
+            .map(|q| q.question.question)
+            .collect();
+        assert_eq!(selected, vec!["wrong", "old", "new"]);
+
+        assert_eq!(schedule(&pool, &log, now, 1).len(), 1);
+    }
+}
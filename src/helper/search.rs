@@ -0,0 +1,192 @@
+//helper/search.rs
+// Keyword and phrase search over question text, options and hints.
+// With `--source sqlite` the FTS5 index of questions.db is used, otherwise a small
+// in-memory index over the loaded pool.
+use colored::*;
+use crate::questions::Question;
+
+/// Weight of a match in the question text compared to one in an option or the hint.
+const QUESTION_WEIGHT: f64 = 3.0;
+
+/// A question that matched every search term.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub question: Question,
+    pub score: f64,
+}
+
+/// Splits a query into lower-cased terms; `"double quoted"` parts stay one phrase.
+pub fn parse_query(query: &str) -> Vec<String> {
+    let mut terms = vec![];
+    for (index, part) in query.split('"').enumerate() {
+        if index % 2 == 1 {
+            let phrase = part.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+            if !phrase.is_empty() {
+                terms.push(phrase);
+            }
+        } else {
+            terms.extend(part.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).map(str::to_lowercase));
+        }
+    }
+    terms
+}
+
+/// FTS5 match expression for the terms: all of them must match, single words also as prefix.
+pub fn fts_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| {
+            let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+            if term.contains(' ') { quoted } else { quoted + "*" }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Lower-cased searchable fields of every question of the pool.
+pub struct SearchIndex<'a> {
+    entries: Vec<(&'a Question, String, String, String)>,
+}
+
+impl<'a> SearchIndex<'a> {
+    pub fn new(pool: &'a [Question]) -> Self {
+        let entries = pool
+            .iter()
+            .map(|q| {
+                (
+                    q,
+                    q.question.to_lowercase(),
+                    q.options.join("\n").to_lowercase(),
+                    q.hint.as_deref().unwrap_or("").to_lowercase(),
+                )
+            })
+            .collect();
+        SearchIndex { entries }
+    }
+
+    /// Questions containing every term, best first. A term scores by how often it occurs,
+    /// matches in the question text count `QUESTION_WEIGHT` times.
+    pub fn search(&self, terms: &[String], limit: usize) -> Vec<SearchHit> {
+        if terms.is_empty() {
+            return vec![];
+        }
+        let mut hits: Vec<SearchHit> = vec![];
+        for (question, text, options, hint) in &self.entries {
+            let mut score = 0.0;
+            let mut all_found = true;
+            for term in terms {
+                let found = text.matches(term.as_str()).count() as f64 * QUESTION_WEIGHT
+                    + options.matches(term.as_str()).count() as f64
+                    + hint.matches(term.as_str()).count() as f64;
+                if found == 0.0 {
+                    all_found = false;
+                    break;
+                }
+                score += found;
+            }
+            if all_found {
+                hits.push(SearchHit { question: (*question).clone(), score });
+            }
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Marks every (case-insensitive) occurrence of the terms in `text`.
+pub fn highlight(text: &str, terms: &[String]) -> String {
+    let lower = text.to_lowercase();
+    // lower-casing can change byte lengths outside ASCII; fall back to no highlighting then
+    if lower.len() != text.len() {
+        return text.to_string();
+    }
+    let mut marked = vec![false; text.len()];
+    for term in terms.iter().filter(|t| !t.is_empty()) {
+        for (start, _) in lower.match_indices(term.as_str()) {
+            marked[start..start + term.len()].iter_mut().for_each(|m| *m = true);
+        }
+    }
+    let mut out = String::new();
+    let mut current = String::new();
+    let mut current_marked = false;
+    for (index, ch) in text.char_indices() {
+        if marked[index] != current_marked && !current.is_empty() {
+            out.push_str(&flush(&current, current_marked));
+            current.clear();
+        }
+        current_marked = marked[index];
+        current.push(ch);
+    }
+    out.push_str(&flush(&current, current_marked));
+    out
+}
+
+fn flush(part: &str, marked: bool) -> String {
+    if marked { part.yellow().bold().to_string() } else { part.to_string() }
+}
+
+fn contains_any(text: &str, terms: &[String]) -> bool {
+    let lower = text.to_lowercase();
+    terms.iter().any(|t| lower.contains(t.as_str()))
+}
+
+pub fn print_hits(hits: &[SearchHit], terms: &[String]) {
+    if hits.is_empty() {
+        println!("{}", "No matching questions.".yellow());
+        return;
+    }
+    for (rank, hit) in hits.iter().enumerate() {
+        println!("\n{} {} {}", format!("{:>3}.", rank + 1).bold(), format!("[{}]", hit.question.id).dimmed(),
+                 format!("score {:.1}", hit.score).dimmed());
+        println!("     {}", highlight(&hit.question.question, terms));
+        for option in hit.question.options.iter().filter(|o| contains_any(o, terms)) {
+            println!("       - {}", highlight(option, terms));
+        }
+        if let Some(hint) = hit.question.hint.as_deref().filter(|h| contains_any(h, terms)) {
+            println!("       hint: {}", highlight(hint, terms));
+        }
+    }
+    println!("\n{} matching questions shown.", hits.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(id: &str, text: &str, options: &[&str], hint: Option<&str>) -> Question {
+        Question {
+            id: id.to_string(),
+            question: text.to_string(),
+            options: options.iter().map(|o| o.to_string()).collect(),
+            hint: hint.map(str::to_string),
+            ..Question::new()
+        }
+    }
+
+    #[test]
+    fn test_parse_query_keeps_phrases() {
+        assert_eq!(parse_query(r#"DNSSEC "Zone  Transfer" nmap"#), vec!["dnssec", "zone transfer", "nmap"]);
+        assert_eq!(fts_query(&parse_query(r#"dns "zone transfer""#)), r#""dns"* "zone transfer""#);
+    }
+
+    #[test]
+    fn test_in_memory_search_ranks_question_text_first() {
+        let pool = vec![
+            question("hint", "Which record type is used?", &["A. MX", "B. TXT"], Some("Think of DNSSEC")),
+            question("text", "What does DNSSEC protect against?", &["A. Cache poisoning", "B. DDoS"], None),
+            question("none", "Which port does SSH use?", &["A. 22", "B. 23"], None),
+        ];
+        let index = SearchIndex::new(&pool);
+        let ids: Vec<String> = index.search(&parse_query("dnssec"), 10).into_iter().map(|h| h.question.id).collect();
+        assert_eq!(ids, vec!["text", "hint"]);
+        assert!(index.search(&parse_query("dnssec ssh"), 10).is_empty());
+        assert_eq!(index.search(&parse_query("\"cache poisoning\""), 10)[0].question.id, "text");
+    }
+
+    #[test]
+    fn test_highlight_marks_matches_only() {
+        let marked = highlight("DNS and more dns", &["dns".to_string()]);
+        assert_eq!(marked, format!("{} and more {}", "DNS".yellow().bold(), "dns".yellow().bold()));
+    }
+}
@@ -0,0 +1,232 @@
+//helper/session.rs
+// The state of an unfinished quiz for `quiz --resume`: which questions, their option order,
+// the answers so far, the elapsed time and the seed. It is written after every answer, so
+// quitting with X or an interrupt with Ctrl-C loses at most the question on screen.
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, SystemTime};
+use serde::{Deserialize, Serialize};
+use crate::questions::{Answer, Outcome, TypedQuestion};
+use super::bank;
+use super::exam::ExamSession;
+use super::history::unix_seconds;
+use super::quiz::{QuestionResult, QuizMode, QuizOptions};
+use super::shuffle::{Presentation, Shuffle};
+
+/// An answer of a practice or review session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedAnswer {
+    pub outcome: Outcome,
+    pub chosen: Option<String>,
+    pub hint_used: bool,
+    pub elapsed_ms: u64,
+}
+
+impl SavedAnswer {
+    pub fn answer(&self) -> Answer {
+        Answer { outcome: self.outcome, chosen: self.chosen.clone(), hint_used: self.hint_used }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub mode: QuizMode,
+    pub seed: u64,
+    pub pass_mark: f64,
+    pub time_limit_secs: u64,
+    #[serde(default)]
+    pub retry_wrong: bool,
+    pub question_ids: Vec<String>,
+    /// Option order of every question, see `Presentation::order`.
+    pub orders: Vec<Vec<usize>>,
+    pub elapsed_ms: u64,
+    pub saved_at: i64,
+    /// Session row in questions.db the answers are recorded in (use_db).
+    #[serde(default)]
+    pub history_session: Option<i64>,
+    /// Practice and review: the answers given so far, in question order.
+    #[serde(default)]
+    pub answers: Vec<SavedAnswer>,
+    /// Exam: selected display indexes, flags, time per question and the question on screen.
+    #[serde(default)]
+    pub selections: Vec<Option<Vec<usize>>>,
+    #[serde(default)]
+    pub flagged: Vec<bool>,
+    #[serde(default)]
+    pub time_spent_ms: Vec<u64>,
+    #[serde(default)]
+    pub current: usize,
+}
+
+impl SavedSession {
+    pub fn new(questions: &[TypedQuestion], presentations: &[Presentation], options: &QuizOptions) -> Self {
+        SavedSession {
+            mode: options.mode,
+            seed: options.shuffle.seed,
+            pass_mark: options.pass_mark,
+            time_limit_secs: options.time_limit.as_secs(),
+            retry_wrong: options.retry_wrong,
+            question_ids: questions.iter().map(|q| q.question.id.clone()).collect(),
+            orders: presentations.iter().map(|p| p.order.clone()).collect(),
+            elapsed_ms: 0,
+            saved_at: 0,
+            history_session: None,
+            answers: vec![],
+            selections: vec![],
+            flagged: vec![],
+            time_spent_ms: vec![],
+            current: 0,
+        }
+    }
+
+    /// The options the session was started with; the questions are already chosen.
+    pub fn options(&self, shuffle: &Shuffle) -> QuizOptions {
+        QuizOptions {
+            num_questions: self.question_ids.len(),
+            pass_mark: self.pass_mark,
+            mode: self.mode,
+            time_limit: Duration::from_secs(self.time_limit_secs),
+            domains: vec![],
+            shuffle: Shuffle { seed: self.seed, ..*shuffle },
+            retry_wrong: self.retry_wrong,
+            bookmarked: false,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.elapsed_ms)
+    }
+
+    /// Looks the saved questions up in the pool and shows their options in the saved order.
+    /// A question whose options changed since is shown in stored order.
+    pub fn restore(&self, pool: &[TypedQuestion]) -> anyhow::Result<(Vec<TypedQuestion>, Vec<Presentation>)> {
+        let by_id: HashMap<&str, &TypedQuestion> = pool.iter().map(|q| (q.question.id.as_str(), q)).collect();
+        let mut questions = vec![];
+        let mut presentations = vec![];
+        for (id, order) in self.question_ids.iter().zip(&self.orders) {
+            let typed = by_id
+                .get(id.as_str())
+                .ok_or_else(|| anyhow::anyhow!("question {} of the saved quiz is no longer in the question bank", id))?;
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            let order = if sorted == (0..typed.question.options.len()).collect::<Vec<_>>() {
+                order.clone()
+            } else {
+                (0..typed.question.options.len()).collect()
+            };
+            presentations.push(Presentation::with_order(&typed.question, order));
+            questions.push((*typed).clone());
+        }
+        Ok((questions, presentations))
+    }
+
+    /// Remembers the answers of a practice or review run.
+    pub fn capture_answers(&mut self, results: &[QuestionResult], elapsed: Duration) {
+        self.answers = results
+            .iter()
+            .map(|r| SavedAnswer {
+                outcome: r.outcome,
+                chosen: r.chosen.clone(),
+                hint_used: r.hint_used,
+                elapsed_ms: r.elapsed.as_millis() as u64,
+            })
+            .collect();
+        self.elapsed_ms = elapsed.as_millis() as u64;
+    }
+
+    pub fn capture_exam(&mut self, session: &ExamSession, elapsed: Duration) {
+        self.selections = session.selections.clone();
+        self.flagged = session.flagged.clone();
+        self.time_spent_ms = session.time_spent.iter().map(|t| t.as_millis() as u64).collect();
+        self.current = session.current;
+        self.elapsed_ms = elapsed.as_millis() as u64;
+    }
+
+    /// Puts the saved exam answers back into a fresh exam session.
+    pub fn restore_exam(&self, session: &mut ExamSession) {
+        if self.selections.len() == session.len() {
+            session.selections = self.selections.clone();
+            session.flagged = self.flagged.clone();
+            session.time_spent = self.time_spent_ms.iter().map(|&ms| Duration::from_millis(ms)).collect();
+            session.current = self.current.min(session.len().saturating_sub(1));
+        }
+    }
+}
+
+pub fn save(session: &mut SavedSession) -> anyhow::Result<()> {
+    session.saved_at = unix_seconds(SystemTime::now());
+    // written after every answer, the bank backups are not needed for this file
+    bank::write_replace(&bank::session_path(), &serde_json::to_vec_pretty(session)?)
+}
+
+/// Saves and reports failures without interrupting the quiz.
+pub fn checkpoint(session: &mut SavedSession) {
+    if let Err(e) = save(session) {
+        eprintln!("Could not save the quiz session: {}", e);
+    }
+}
+
+pub fn load() -> anyhow::Result<Option<SavedSession>> {
+    let path = bank::session_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path)?;
+    let session = serde_json::from_str(&text).map_err(|e| anyhow::anyhow!("{} is damaged: {}", path.display(), e))?;
+    Ok(Some(session))
+}
+
+/// Forgets the saved session once the quiz is finished.
+pub fn clear() {
+    let path = bank::session_path();
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            eprintln!("Could not remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::{Question, QuestionType};
+
+    fn typed(id: &str, options: &[&str]) -> TypedQuestion {
+        let question = Question {
+            id: id.to_string(),
+            question: format!("Question {}", id),
+            options: options.iter().map(|o| o.to_string()).collect(),
+            answer: options[0].to_string(),
+            ..Question::new()
+        };
+        TypedQuestion { qtype: QuestionType::DefaultItem, question }
+    }
+
+    #[test]
+    fn test_restore_keeps_order_and_survives_json() {
+        let pool = vec![typed("a", &["1", "2", "3"]), typed("b", &["x", "y"])];
+        let presentations = vec![Presentation::with_order(&pool[1].question, vec![1, 0]), Presentation::with_order(&pool[0].question, vec![2, 0, 1])];
+        let selected = vec![pool[1].clone(), pool[0].clone()];
+        let options = QuizOptions { shuffle: Shuffle { seed: 9, enabled: true, pin: true }, ..QuizOptions::default() };
+        let mut saved = SavedSession::new(&selected, &presentations, &options);
+        saved.answers.push(SavedAnswer { outcome: Outcome::Wrong, chosen: Some("y".to_string()), hint_used: true, elapsed_ms: 1500 });
+        saved.elapsed_ms = 1500;
+
+        let saved: SavedSession = serde_json::from_str(&serde_json::to_string(&saved).unwrap()).unwrap();
+        let (questions, restored) = saved.restore(&pool).unwrap();
+        assert_eq!(questions.iter().map(|q| q.question.id.as_str()).collect::<Vec<_>>(), vec!["b", "a"]);
+        assert_eq!(restored[0].question.options, vec!["y", "x"]);
+        assert_eq!(restored[1].correct, vec![1]);
+        assert_eq!(saved.options(&Shuffle::off()).shuffle.seed, 9);
+        assert_eq!(saved.answers[0].answer().outcome, Outcome::Wrong);
+    }
+
+    #[test]
+    fn test_restore_fails_for_removed_and_resets_changed_questions() {
+        let pool = vec![typed("a", &["1", "2", "3"])];
+        let presentations = vec![Presentation::with_order(&pool[0].question, vec![1, 0])];
+        let saved = SavedSession::new(&pool, &presentations, &QuizOptions::default());
+        assert_eq!(saved.restore(&pool).unwrap().1[0].order, vec![0, 1, 2]);
+        assert!(saved.restore(&[typed("other", &["1", "2"])]).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//helper/shuffle.rs
+// Per-session option order. Every question gets its own permutation derived from the
+// session seed and the question id, so the same seed shows the same quiz again. The
+// correct options are tracked by index through the permutation, not looked up by text.
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use crate::questions::{fnv1a, Question};
+
+/// Lower-cased options that only make sense as the last choice.
+const PINNED_OPTIONS: &[&str] = &["all of the above", "none of the above", "all of these", "none of these", "all of them", "none of them"];
+
+/// How options are ordered in a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shuffle {
+    pub seed: u64,
+    pub enabled: bool,
+    /// Keep "All of the above" / "None of the above" at the end.
+    pub pin: bool,
+}
+
+impl Shuffle {
+    /// Stored order, e.g. for tests or `--no_shuffle`.
+    pub fn off() -> Self {
+        Shuffle { seed: 0, enabled: false, pin: true }
+    }
+}
+
+pub fn is_pinned(option: &str) -> bool {
+    let option = option.trim().trim_end_matches('.').to_lowercase();
+    PINNED_OPTIONS.contains(&option.as_str())
+}
+
+/// Options like "Both A and B" point at other options by position; such questions keep their order.
+fn refers_to_positions(options: &[String]) -> bool {
+    options.iter().any(|o| {
+        let words: Vec<String> = o.split_whitespace().map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()).collect();
+        words.windows(3).any(|w| w[0].len() == 1 && w[1] == "and" && w[2].len() == 1)
+            || words.iter().any(|w| w == "above" || w == "below") && !is_pinned(o)
+    })
+}
+
+/// Per-question seed, so the order does not depend on where the question comes in the quiz.
+fn question_seed(seed: u64, id: &str) -> u64 {
+    seed ^ fnv1a(id.bytes())
+}
+
+/// Display position -> stored option index.
+pub fn option_order(question: &Question, shuffle: &Shuffle) -> Vec<usize> {
+    let count = question.options.len();
+    if !shuffle.enabled || refers_to_positions(&question.options) {
+        return (0..count).collect();
+    }
+    let (mut free, pinned): (Vec<usize>, Vec<usize>) =
+        (0..count).partition(|&i| !shuffle.pin || !is_pinned(&question.options[i]));
+    free.shuffle(&mut StdRng::seed_from_u64(question_seed(shuffle.seed, &question.id)));
+    free.extend(pinned);
+    free
+}
+
+/// A question as it is shown: options in display order and the correct ones by display index.
+#[derive(Debug, Clone)]
+pub struct Presentation {
+    pub question: Question,
+    /// `order[display]` is the index of the option in the stored question.
+    pub order: Vec<usize>,
+    pub correct: Vec<usize>,
+}
+
+impl Presentation {
+    pub fn new(question: &Question, shuffle: &Shuffle) -> Self {
+        Self::with_order(question, option_order(question, shuffle))
+    }
+
+    /// Shows `question` in a known order, e.g. one restored from a saved session.
+    pub fn with_order(question: &Question, order: Vec<usize>) -> Self {
+        let stored_correct = question.correct_indexes();
+        let correct = (0..order.len()).filter(|display| stored_correct.contains(&order[*display])).collect();
+        let mut shown = question.clone();
+        shown.options = order.iter().map(|&i| question.options[i].clone()).collect();
+        Presentation { question: shown, order, correct }
+    }
+
+    /// True if the selected display indexes are exactly the correct options.
+    pub fn is_correct(&self, selection: &[usize]) -> bool {
+        let mut selection = selection.to_vec();
+        selection.sort_unstable();
+        selection == self.correct
+    }
+
+    /// The texts of the selected options, as stored in the history.
+    pub fn chosen(&self, selection: &[usize]) -> String {
+        selection.iter().filter_map(|&i| self.question.options.get(i)).map(String::as_str).collect::<Vec<_>>().join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(id: &str, options: &[&str], answer: &str) -> Question {
+        Question {
+            id: id.to_string(),
+            question: "Which one?".to_string(),
+            options: options.iter().map(|o| o.to_string()).collect(),
+            answer: answer.to_string(),
+            ..Question::new()
+        }
+    }
+
+    fn shuffle(seed: u64) -> Shuffle {
+        Shuffle { seed, enabled: true, pin: true }
+    }
+
+    #[test]
+    fn test_same_seed_same_order_and_correct_tracked() {
+        let q = question("q1", &["a", "b", "c", "d", "e", "f"], "c");
+        let first = Presentation::new(&q, &shuffle(7));
+        assert_eq!(first.order, Presentation::new(&q, &shuffle(7)).order);
+        assert!((0..50).any(|seed| Presentation::new(&q, &shuffle(seed)).order != first.order));
+        let mut sorted = first.order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(first.correct.len(), 1);
+        assert_eq!(first.question.options[first.correct[0]], "c");
+        assert!(first.is_correct(&first.correct));
+        assert_eq!(first.chosen(&first.correct), "c");
+    }
+
+    #[test]
+    fn test_pinned_options_stay_last() {
+        let q = question("q2", &["None of the above", "a", "b", "c", "All of the above."], "a");
+        for seed in 0..20 {
+            let shown = Presentation::new(&q, &shuffle(seed)).question.options;
+            assert_eq!(shown[3..], ["None of the above", "All of the above."]);
+        }
+        let unpinned = Shuffle { pin: false, ..shuffle(0) };
+        assert!((0..20).any(|seed| !is_pinned(&Presentation::new(&q, &Shuffle { seed, ..unpinned }).question.options[4])));
+    }
+
+    #[test]
+    fn test_positional_options_and_disabled_keep_order() {
+        let q = question("q3", &["TCP", "UDP", "Both A and B", "ICMP"], "Both A and B");
+        assert_eq!(Presentation::new(&q, &shuffle(3)).order, vec![0, 1, 2, 3]);
+        let q = question("q4", &["a", "b", "c"], "b");
+        let shown = Presentation::new(&q, &Shuffle::off());
+        assert_eq!((shown.order, shown.correct), (vec![0, 1, 2], vec![1]));
+    }
+}
@@ -0,0 +1,236 @@
+//helper/source.rs
+// Where the question pool comes from: the local JSON banks, the SQLite questions table
+// written by `db sync`, or a remote endpoint serving the same JSON array.
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use crate::questions::{self, Question};
+use super::{bank, htr_low_level_http};
+
+/// Backend selected with `--source`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceKind {
+    Json,
+    Sqlite,
+    Http(String),
+}
+
+impl SourceKind {
+    /// JSON banks in local mode, otherwise the SQLite table when the binary has it.
+    pub fn default_for_features() -> Self {
+        if super::is_use_local() || !super::is_use_db() {
+            SourceKind::Json
+        } else {
+            SourceKind::Sqlite
+        }
+    }
+}
+
+impl FromStr for SourceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(SourceKind::Json),
+            "sqlite" => Ok(SourceKind::Sqlite),
+            url if url.starts_with("http://") => Ok(SourceKind::Http(url.to_string())),
+            url if url.starts_with("https://") => Err("https is not supported, use an http:// URL".to_string()),
+            other => Err(format!("unknown question source '{}', expected json, sqlite or an http:// URL", other)),
+        }
+    }
+}
+
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceKind::Json => write!(f, "json"),
+            SourceKind::Sqlite => write!(f, "sqlite"),
+            SourceKind::Http(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// Anything that can hand out the full question pool.
+pub trait QuestionSource {
+    /// Short description for messages, e.g. the file or URL.
+    fn name(&self) -> String;
+    fn load(&self) -> anyhow::Result<Vec<Question>>;
+
+    /// Stores a new question; read-only sources refuse.
+    fn add(&self, _question: Question) -> anyhow::Result<()> {
+        anyhow::bail!("{} is read-only", self.name())
+    }
+
+    /// Stores several new questions, e.g. from a bulk import.
+    fn add_all(&self, questions: Vec<Question>) -> anyhow::Result<()> {
+        questions.into_iter().try_for_each(|q| self.add(q))
+    }
+
+    /// Replaces the stored question with the same id.
+    fn update(&self, _question: Question) -> anyhow::Result<()> {
+        anyhow::bail!("{} is read-only", self.name())
+    }
+}
+
+/// The configured `--bank` files, merged in order.
+pub struct JsonSource {
+    pub paths: Vec<PathBuf>,
+}
+
+impl QuestionSource for JsonSource {
+    fn name(&self) -> String {
+        self.paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")
+    }
+
+    fn load(&self) -> anyhow::Result<Vec<Question>> {
+        let mut banks = vec![];
+        for path in &self.paths {
+            match questions::load_bank(path) {
+                Ok(questions) => banks.push(questions),
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+        let (questions, shadowed) = bank::merge_banks(banks);
+        if shadowed > 0 {
+            eprintln!("Note: {} questions appear in several banks, the first bank wins.", shadowed);
+        }
+        Ok(questions)
+    }
+
+    /// New questions go to the primary bank only.
+    fn add(&self, question: Question) -> anyhow::Result<()> {
+        questions::add_question_to_json(question)
+    }
+
+    fn add_all(&self, questions: Vec<Question>) -> anyhow::Result<()> {
+        questions::add_questions_to_json(questions)
+    }
+
+    fn update(&self, question: Question) -> anyhow::Result<()> {
+        questions::update_question_in_json(question)
+    }
+}
+
+/// The normalized question tables of the database next to the primary bank.
+pub struct SqliteSource {
+    pub path: PathBuf,
+}
+
+impl QuestionSource for SqliteSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    #[cfg(feature = "use_db")]
+    fn load(&self) -> anyhow::Result<Vec<Question>> {
+        super::repository::QuestionRepository::with_connection(rusqlite::Connection::open(&self.path)?)?.list()
+    }
+
+    #[cfg(feature = "use_db")]
+    fn add(&self, mut question: Question) -> anyhow::Result<()> {
+        let repo = super::repository::QuestionRepository::with_connection(rusqlite::Connection::open(&self.path)?)?;
+        if question.id.is_empty() {
+            question.id = questions::content_id(&question);
+        }
+        repo.insert(&question)
+    }
+
+    #[cfg(feature = "use_db")]
+    fn update(&self, question: Question) -> anyhow::Result<()> {
+        let repo = super::repository::QuestionRepository::with_connection(rusqlite::Connection::open(&self.path)?)?;
+        if !repo.update(&question)? {
+            anyhow::bail!("no question with id {} in {}", question.id, self.name());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "use_db"))]
+    fn load(&self) -> anyhow::Result<Vec<Question>> {
+        anyhow::bail!("this binary was built without SQLite support, rebuild with --features use_db")
+    }
+}
+
+/// A URL answering GET with the JSON array of questions, the same format as a bank file.
+pub struct HttpSource {
+    pub url: String,
+}
+
+impl QuestionSource for HttpSource {
+    fn name(&self) -> String {
+        self.url.clone()
+    }
+
+    fn load(&self) -> anyhow::Result<Vec<Question>> {
+        let response = htr_low_level_http::http_get(&self.url)
+            .map_err(|e| anyhow::anyhow!("Request to {} failed: {}", self.url, e))?;
+        let status = response.status_line().split_whitespace().nth(1).unwrap_or("");
+        if status != "200" {
+            anyhow::bail!("{} answered with {}", self.url, response.status_line());
+        }
+        let mut questions: Vec<Question> = serde_json::from_str(response.body())
+            .map_err(|e| anyhow::anyhow!("{} did not return a question list: {}", self.url, e))?;
+        for question in questions.iter_mut() {
+            question.source = Some(self.url.clone());
+        }
+        Ok(questions)
+    }
+}
+
+pub fn open_source(kind: &SourceKind) -> Box<dyn QuestionSource> {
+    match kind {
+        SourceKind::Json => Box::new(JsonSource { paths: bank::bank_paths() }),
+        SourceKind::Sqlite => Box::new(SqliteSource { path: bank::db_path() }),
+        SourceKind::Http(url) => Box::new(HttpSource { url: url.clone() }),
+    }
+}
+
+/// The source chosen on the command line.
+pub fn configured_source() -> Box<dyn QuestionSource> {
+    open_source(&super::get_app_config().source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Answers a single request with `response` and returns the URL to ask.
+    fn serve_once(response: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://127.0.0.1:{}/questions.json", listener.local_addr().unwrap().port());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn test_http_source_loads_questions() {
+        let body = r#"[{"id":"abc","question":"Which port does SSH use?","options":["A. 21","B. 22"],"answer":"B. 22"}]"#;
+        let chunked = format!("{:x}\r\n{}\r\n0\r\n\r\n", body.len(), body);
+        let url = serve_once(format!("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{}", chunked));
+        let questions = HttpSource { url: url.clone() }.load().unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].id, "abc");
+        assert_eq!(questions[0].source.as_deref(), Some(url.as_str()));
+    }
+
+    #[test]
+    fn test_http_source_rejects_error_status() {
+        let url = serve_once("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string());
+        assert!(HttpSource { url }.load().is_err());
+    }
+
+    #[test]
+    fn test_parse_source_kind() {
+        assert_eq!("json".parse::<SourceKind>(), Ok(SourceKind::Json));
+        assert_eq!("http://localhost/q".parse::<SourceKind>(), Ok(SourceKind::Http("http://localhost/q".to_string())));
+        assert!("https://example.com".parse::<SourceKind>().is_err());
+        assert!("mongo".parse::<SourceKind>().is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//helper/stats.rs
+// Accuracy breakdowns per domain and difficulty, used by the quiz report and the `stats` subcommand.
+use std::collections::BTreeMap;
+use colored::*;
+use crate::questions::{find_question, Difficulty, Question};
+use super::domains;
+use super::history;
+
+/// Tag used for questions without any domain.
+pub const UNTAGGED: &str = "untagged";
+/// Label for questions without a difficulty rating.
+pub const UNRATED: &str = "unrated";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub correct: usize,
+    pub total: usize,
+}
+
+impl Tally {
+    pub fn add(&mut self, correct: bool) {
+        self.total += 1;
+        if correct {
+            self.correct += 1;
+        }
+    }
+
+    pub fn percentage(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.correct as f64 * 100.0 / self.total as f64
+    }
+}
+
+/// Accuracy per label (domain, difficulty, ...), sorted by label.
+#[derive(Debug, Clone, Default)]
+pub struct Breakdown {
+    pub rows: BTreeMap<String, Tally>,
+}
+
+impl Breakdown {
+    pub fn add(&mut self, label: &str, correct: bool) {
+        self.rows.entry(label.to_string()).or_default().add(correct);
+    }
+
+    /// Counts an answer once for every domain of the question.
+    pub fn add_by_domain(&mut self, question: &Question, correct: bool) {
+        let tags = domains::effective_tags(question);
+        if tags.is_empty() {
+            self.add(UNTAGGED, correct);
+        }
+        for tag in tags {
+            self.add(&tag, correct);
+        }
+    }
+
+    pub fn add_by_difficulty(&mut self, question: &Question, correct: bool) {
+        self.add(difficulty_label(question.difficulty), correct);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Prints a colored table, weakest rows first: green at or above the pass mark,
+    /// yellow within 15 points below it, red otherwise.
+    pub fn print(&self, title: &str, pass_mark: f64) {
+        let mut rows: Vec<(&String, &Tally)> = self.rows.iter().collect();
+        rows.sort_by(|a, b| a.1.percentage().total_cmp(&b.1.percentage()).then(a.0.cmp(b.0)));
+        println!("\n{:<24} {:>8} {:>8} {:>9}", title.bold(), "correct", "asked", "accuracy");
+        for (label, tally) in rows {
+            let accuracy = format!("{:>8.1}%", tally.percentage());
+            let accuracy = if tally.percentage() >= pass_mark {
+                accuracy.green()
+            } else if tally.percentage() >= pass_mark - 15.0 {
+                accuracy.yellow()
+            } else {
+                accuracy.red()
+            };
+            println!("{:<24} {:>8} {:>8} {}", label, tally.correct, tally.total, accuracy);
+        }
+    }
+}
+
+pub fn difficulty_label(difficulty: Option<Difficulty>) -> &'static str {
+    match difficulty {
+        Some(Difficulty::Easy) => "easy",
+        Some(Difficulty::Medium) => "medium",
+        Some(Difficulty::Hard) => "hard",
+        None => UNRATED,
+    }
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD HH:MM` (UTC).
+pub fn format_timestamp(unix: i64) -> String {
+    let days = unix.div_euclid(86_400);
+    let secs = unix.rem_euclid(86_400);
+    // civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, secs / 3600, secs % 3600 / 60)
+}
+
+/// Accuracy per domain and per difficulty over the whole answer history.
+pub fn history_breakdowns(pool: &[Question]) -> anyhow::Result<(Breakdown, Breakdown)> {
+    let log = history::load_answer_log()?;
+    let mut by_domain = Breakdown::default();
+    let mut by_difficulty = Breakdown::default();
+    for (key, events) in &log {
+        let probe = Question { id: key.clone(), question: key.clone(), ..Question::new() };
+        let Some(question) = find_question(pool, &probe) else {
+            continue;
+        };
+        for event in events {
+            by_domain.add_by_domain(question, event.correct);
+            by_difficulty.add_by_difficulty(question, event.correct);
+        }
+    }
+    Ok((by_domain, by_difficulty))
+}
+
+/// The `stats` subcommand: breakdowns over all recorded answers and the trend of the last sessions.
+pub fn print_stats(pool: &[Question], sessions: usize, pass_mark: f64) -> anyhow::Result<()> {
+    let (by_domain, by_difficulty) = history_breakdowns(pool)?;
+    if by_domain.is_empty() {
+        println!("No answers recorded yet. Take a quiz first (history needs the use_db feature).");
+        return Ok(());
+    }
+    by_domain.print("Domain", pass_mark);
+    by_difficulty.print("Difficulty", pass_mark);
+
+    let recent = history::load_sessions(sessions)?;
+    println!("\n{:<24} {:>8} {:>8} {:>9}", format!("Last {} sessions", recent.len()).bold(), "correct", "asked", "score");
+    let mut previous: Option<f64> = None;
+    for session in recent.iter().rev() {
+        let trend = match previous {
+            Some(p) if session.score > p => "▲".green(),
+            Some(p) if session.score < p => "▼".red(),
+            Some(_) => "=".normal(),
+            None => " ".normal(),
+        };
+        let score = format!("{:>8.1}%", session.score);
+        let score = if session.score >= session.pass_mark { score.green() } else { score.red() };
+        println!("{:<24} {:>8} {:>8} {} {}", format_timestamp(session.started_at), session.correct, session.total, score, trend);
+        previous = Some(session.score);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breakdown_counts_every_domain() {
+        let question = Question {
+            question: "Which tool cracks WEP keys?".to_string(),
+            tags: vec!["wireless".to_string(), "cryptography".to_string()],
+            difficulty: Some(Difficulty::Hard),
+            ..Question::new()
+        };
+        let untagged = Question { question: "Lorem ipsum".to_string(), ..Question::new() };
+        let mut by_domain = Breakdown::default();
+        let mut by_difficulty = Breakdown::default();
+        for (q, correct) in [(&question, true), (&question, false), (&untagged, true)] {
+            by_domain.add_by_domain(q, correct);
+            by_difficulty.add_by_difficulty(q, correct);
+        }
+        assert_eq!(by_domain.rows["wireless"], Tally { correct: 1, total: 2 });
+        assert_eq!(by_domain.rows["cryptography"].percentage(), 50.0);
+        assert_eq!(by_domain.rows[UNTAGGED].total, 1);
+        assert_eq!(by_difficulty.rows["hard"].total, 2);
+        assert_eq!(by_difficulty.rows[UNRATED].total, 1);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(1_730_000_000), "2024-10-27 03:33");
+    }
+}
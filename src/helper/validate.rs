@@ -0,0 +1,324 @@
+//helper/validate.rs
+// Consistency checks for a question bank, used by the `validate` subcommand to gate changes.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use colored::*;
+use crate::questions::{check_for_duplicates, Question, QuestionType};
+
+/// Option counts we accept; `ask_question` numbers any amount, more than ten is not readable anymore.
+pub const MIN_OPTIONS: usize = 2;
+pub const MAX_OPTIONS: usize = 10;
+/// Word overlap (Jaccard index) from which two different texts count as near duplicates.
+pub const NEAR_DUPLICATE_THRESHOLD: f64 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Position in the bank (0 based), `None` for file level problems.
+    pub index: Option<usize>,
+    pub id: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Error => "error".red().bold(),
+            Severity::Warning => "warning".yellow().bold(),
+        };
+        match self.index {
+            Some(index) => write!(f, "{} #{} [{}]: {}", label, index + 1, self.id, self.message),
+            None => write!(f, "{}: {}", label, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub checked: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    fn push(&mut self, severity: Severity, index: Option<usize>, question: Option<&Question>, message: String) {
+        self.issues.push(Issue {
+            severity,
+            index,
+            id: question.map(|q| q.id.clone()).unwrap_or_default(),
+            message,
+        });
+    }
+
+    pub fn errors(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == Severity::Error).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning).count()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.errors() == 0
+    }
+
+    pub fn print(&self) {
+        for issue in &self.issues {
+            println!("{}", issue);
+        }
+        let summary = format!("Checked {} questions: {} errors, {} warnings", self.checked, self.errors(), self.warnings());
+        if self.is_ok() {
+            println!("{}", summary.green());
+        } else {
+            println!("{}", summary.red());
+        }
+    }
+}
+
+/// Reads and validates a bank file; unreadable or non UTF-8 content is reported, not returned as `Err`.
+pub fn validate_file(path: &Path) -> anyhow::Result<ValidationReport> {
+    let bytes = fs::read(path)?;
+    let mut report = ValidationReport::default();
+    let data = match std::str::from_utf8(&bytes) {
+        Ok(data) => data,
+        Err(e) => {
+            report.push(Severity::Error, None, None, format!("{} is not valid UTF-8: {}", path.display(), e));
+            return Ok(report);
+        }
+    };
+    match serde_json::from_str::<Vec<Question>>(data) {
+        Ok(questions) => Ok(validate_questions(&questions)),
+        Err(e) => {
+            report.push(Severity::Error, None, None, format!("{} does not match the question schema: {}", path.display(), e));
+            Ok(report)
+        }
+    }
+}
+
+pub fn validate_questions(questions: &[Question]) -> ValidationReport {
+    let mut report = ValidationReport { checked: questions.len(), ..Default::default() };
+    let mut seen_ids: HashMap<&str, usize> = HashMap::new();
+
+    for (index, question) in questions.iter().enumerate() {
+        let mut issue = |severity, message: String| report.push(severity, Some(index), Some(question), message);
+
+        if question.id.is_empty() {
+            issue(Severity::Error, "missing id (run `db migrate`)".to_string());
+        } else if let Some(first) = seen_ids.insert(&question.id, index) {
+            issue(Severity::Error, format!("id already used by #{}", first + 1));
+        }
+        if question.question.trim().is_empty() {
+            issue(Severity::Error, "empty question text".to_string());
+        }
+        if question.is_multi_answer() {
+            if question.answers.len() < 2 {
+                issue(Severity::Warning, "select-all question with a single answer, use `answer` instead".to_string());
+            }
+            for answer in question.answers.iter().filter(|a| !question.options.contains(a)) {
+                issue(Severity::Error, format!("answer {:?} is not among the options", answer));
+            }
+            if question.answers.iter().collect::<HashSet<_>>().len() != question.answers.len() {
+                issue(Severity::Error, "answers contain duplicates".to_string());
+            }
+        } else if question.answer.trim().is_empty() {
+            issue(Severity::Error, "empty answer".to_string());
+        } else if !question.options.contains(&question.answer) {
+            issue(Severity::Error, format!("answer {:?} is not among the options", question.answer));
+        }
+        if !(MIN_OPTIONS..=MAX_OPTIONS).contains(&question.options.len()) {
+            issue(Severity::Error, format!("{} options, expected {} to {}", question.options.len(), MIN_OPTIONS, MAX_OPTIONS));
+        }
+        for (i, option) in question.options.iter().enumerate() {
+            if option.trim().is_empty() {
+                issue(Severity::Error, format!("option {} is empty", i + 1));
+            }
+        }
+        let distinct: HashSet<&String> = question.options.iter().collect();
+        if distinct.len() != question.options.len() {
+            issue(Severity::Error, "options contain duplicates".to_string());
+        }
+        if question.hint.as_deref().is_some_and(|h| h.trim().is_empty()) {
+            issue(Severity::Warning, "hint is present but empty".to_string());
+        }
+        // keys go stale when an option text is edited without the distractor notes
+        for option in question.distractors.keys() {
+            if !question.options.contains(option) {
+                issue(Severity::Warning, format!("distractor note for {:?}, which is not an option", option));
+            } else if question.correct_answers().contains(&option.as_str()) {
+                issue(Severity::Warning, format!("distractor note for the correct answer {:?}", option));
+            }
+        }
+
+        let fields = std::iter::once(("question", &question.question))
+            .chain(std::iter::once(("answer", &question.answer)))
+            .chain(question.options.iter().map(|o| ("option", o)))
+            .chain(question.hint.iter().map(|h| ("hint", h)));
+        for (field, text) in fields {
+            if let Some(problem) = whitespace_problem(text) {
+                issue(Severity::Warning, format!("{} {}", field, problem));
+            }
+        }
+    }
+
+    check_duplicates(questions, &mut report);
+    report.issues.sort_by_key(|i| (i.index, std::cmp::Reverse(i.severity)));
+    report
+}
+
+fn whitespace_problem(text: &str) -> Option<&'static str> {
+    if text.is_empty() {
+        None
+    } else if text.trim() != text {
+        Some("has leading or trailing whitespace")
+    } else if text.chars().any(|c| c.is_control() && c != '\n') {
+        Some("contains control characters")
+    } else if text.contains('\u{a0}') || text.contains('\u{200b}') || text.contains('\u{feff}') {
+        Some("contains non-breaking or zero-width spaces")
+    } else if text.contains("  ") {
+        Some("contains repeated spaces")
+    } else {
+        None
+    }
+}
+
+fn check_duplicates(questions: &[Question], report: &mut ValidationReport) {
+    // check_for_duplicates tags copies of an identical entry as DuplicateNodes and entries
+    // that only share the question text as DuplicateQuestions
+    let typed = check_for_duplicates(&questions.to_vec());
+    let texts_of = |qtype: QuestionType| -> HashSet<String> {
+        typed.iter().filter(|t| t.qtype == qtype).map(|t| t.question.question.clone()).collect()
+    };
+    let exact = texts_of(QuestionType::DuplicateNodes);
+    let same_text = texts_of(QuestionType::DuplicateQuestions);
+
+    let mut positions: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, question) in questions.iter().enumerate() {
+        positions.entry(question.question.as_str()).or_default().push(index);
+    }
+    for (text, indexes) in &positions {
+        if indexes.len() < 2 {
+            continue;
+        }
+        let copies = indexes.iter().map(|i| format!("#{}", i + 1)).collect::<Vec<_>>().join(", ");
+        for &index in &indexes[1..] {
+            if exact.contains(*text) {
+                report.push(Severity::Error, Some(index), Some(&questions[index]), format!("exact duplicate question text (copies: {})", copies));
+            } else if same_text.contains(*text) {
+                report.push(Severity::Warning, Some(index), Some(&questions[index]), format!("same question text with different options or answer (copies: {})", copies));
+            }
+        }
+    }
+
+    let words: Vec<HashSet<String>> = questions.iter().map(|q| word_set(&q.question)).collect();
+    for j in 0..questions.len() {
+        let near = (0..j).find(|&i| {
+            questions[i].question != questions[j].question && jaccard(&words[i], &words[j]) >= NEAR_DUPLICATE_THRESHOLD
+        });
+        if let Some(i) = near {
+            let similarity = jaccard(&words[i], &words[j]);
+            report.push(Severity::Warning, Some(j), Some(&questions[j]), format!("near duplicate of #{} ({:.0}% word overlap)", i + 1, similarity * 100.0));
+        }
+    }
+}
+
+fn word_set(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(id: &str, text: &str, answer: &str, options: &[&str]) -> Question {
+        Question {
+            id: id.to_string(),
+            question: text.to_string(),
+            answer: answer.to_string(),
+            options: options.iter().map(|o| o.to_string()).collect(),
+            ..Question::new()
+        }
+    }
+
+    fn messages(report: &ValidationReport) -> Vec<String> {
+        report.issues.iter().map(|i| i.message.clone()).collect()
+    }
+
+    #[test]
+    fn test_clean_bank_passes() {
+        let report = validate_questions(&[
+            question("a", "What does DNSSEC add to DNS?", "Integrity", &["Integrity", "Availability"]),
+            question("b", "Which port does SSH use?", "22", &["21", "22", "23", "25"]),
+        ]);
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.warnings(), 0);
+    }
+
+    #[test]
+    fn test_reports_schema_errors() {
+        let report = validate_questions(&[
+            question("a", "Q1", "E", &["A", "B", "C", "D"]),
+            question("a", "Q2 ", "A", &["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K"]),
+            question("", "", "", &["A", "A"]),
+        ]);
+        let messages = messages(&report);
+        assert!(messages.contains(&"answer \"E\" is not among the options".to_string()));
+        assert!(messages.contains(&"id already used by #1".to_string()));
+        assert!(messages.contains(&"11 options, expected 2 to 10".to_string()));
+        assert!(messages.contains(&"question has leading or trailing whitespace".to_string()));
+        assert!(messages.contains(&"missing id (run `db migrate`)".to_string()));
+        assert!(messages.contains(&"options contain duplicates".to_string()));
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn test_checks_multi_answer_questions() {
+        let multi = Question {
+            answers: vec!["A".to_string(), "X".to_string()],
+            ..question("m", "Select all that apply", "", &["A", "B", "C"])
+        };
+        assert_eq!(messages(&validate_questions(&[multi])), vec!["answer \"X\" is not among the options"]);
+    }
+
+    #[test]
+    fn test_checks_distractor_notes() {
+        let mut q = question("d", "Which port does SSH use?", "22", &["21", "22"]);
+        for (option, why) in [("21", "FTP"), ("22", "SSH"), ("2l", "typo")] {
+            q.distractors.insert(option.to_string(), why.to_string());
+        }
+        assert_eq!(
+            messages(&validate_questions(&[q])),
+            vec!["distractor note for the correct answer \"22\"", "distractor note for \"2l\", which is not an option"]
+        );
+    }
+
+    #[test]
+    fn test_reports_exact_and_near_duplicates() {
+        let report = validate_questions(&[
+            question("a", "Which tool is used for port scanning in Kali Linux?", "nmap", &["nmap", "john"]),
+            question("b", "Which tool is used for port scanning in Kali Linux?", "nmap", &["nmap", "john"]),
+            question("c", "Which tool is used for port scanning in Kali Linux ?!", "nmap", &["nmap", "hydra"]),
+        ]);
+        let messages = messages(&report);
+        assert!(messages.iter().any(|m| m.starts_with("exact duplicate question text")));
+        assert!(messages.iter().any(|m| m.starts_with("near duplicate of #1")));
+        assert_eq!(report.errors(), 1);
+    }
+}
@@ -7,7 +7,7 @@ mod questions;
 use ollama_rs::{generation::{completion::GenerationResponse, completion::request::GenerationRequest, options::GenerationOptions}, Ollama};
 use crate::quiz::get_num_questions;
 use questions::{load_question_pool, check_for_duplicates, TypedQuestion, QuestionType};
-use helper::{htr_low_level_http, apple_say_using, ollama, quiz, banner, build_clap_app, AppConfig};
+use helper::{htr_low_level_http, apple_say_using, ollama, quiz, banner, build_clap_app, AppCommand, AppConfig};
 use crate::questions::Question;
 
 // use quiz::{TypedQuestion};
@@ -31,10 +31,11 @@ fn test_typed_question(){
             question: "What is the capital of Burkina Faso?".to_string(),
             options: vec!["Paris".to_string(), "Ouagadougou".to_string(), "Berlin".to_string(), "Madrid".to_string()],
             answer: "Paris".to_string(),
-            hint: Some("It's a city in France".to_string())
+            hint: Some("It's a city in France".to_string()),
+            ..questions::Question::new()
         }
     };
-    questions::ask_question(1, &typed_question);
+    questions::ask_question(1, &helper::shuffle::Presentation::new(&typed_question.question, &helper::shuffle::Shuffle::off()));
 }
 
 #[allow(dead_code)]
@@ -67,32 +68,11 @@ async fn wuhan_main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-
 fn main() {
     // test_components();
     // htr_low_level_http::check_internet_connection();
     // apple_say_using("欢迎来到黑客测验！", Some("Tingting"));
-    // let num_questions = quiz::get_num_questions(); // in short the :: denominates the functional approach
     // let typed_questions = questions::check_for_duplicates(&question_pool);
     let config = helper::get_app_config();
-
-    if let Err(e) = banner(config) {
-        eprintln!("Error displaying the banner: {}", e);
-    }
-    println!("{:?}", helper::banner(config));
-    #[cfg(feature = "use_clipboard")]{
-        let result = questions::do_clipbboard_actions();
-        #[cfg(feature = "use_clipboard")]
-        dbg!(&result);
-    }
-    #[cfg(feature = "use_sqlite")]{
-        let result = helper::json_to_sqlite();
-        #[cfg(feature = "use_sqlite")]
-        dbg!(&result);
-    }
-    #[cfg(not(feature = "use_clipboard"))]{
-        let question_pool = check_for_duplicates(&load_question_pool());
-        quiz::run_quiz(question_pool, get_num_questions());
-    }
-
+    std::process::exit(helper::commands::run(config));
 }
@@ -6,21 +6,25 @@
 use serde_json::json;
 use serde::{Deserialize, Serialize};
 use colored::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::format;
 use std::fs;
+use std::path::Path;
 use std::io::{self, Write};
-use std::process;
 use std::thread::sleep;
 use std::time::Duration;
-use super::helper::{banner}; // we are one deeper than the helper module, as executed from e.g. main.rs /lib.rs
+use super::helper::{banner, get_app_config}; // we are one deeper than the helper module, as executed from e.g. main.rs /lib.rs
 use rand::prelude::{IndexedRandom}; //, SliceRandom};
 use clipboard::{ClipboardContext, ClipboardProvider};
-use anyhow;
 use crate::{helper, questions};
+use crate::helper::bank;
+use crate::helper::shuffle::Presentation;
 #[cfg(feature = "use_ki")]
 use super::ollama;
 
+/// Model used when a question is filled during the clipboard import.
+pub const DEFAULT_OLLAMA_MODEL: &str = "mistral-large";
+
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Hash)]
 pub struct OllamaJson {
     pub hint: String,
@@ -28,22 +32,181 @@ pub struct OllamaJson {
 }
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Hash)]
 pub struct Question {
+    /// Stable identifier, assigned once from a content hash and kept when the text is edited.
+    #[serde(default)]
+    pub id: String,
     pub question: String,
     pub answer: String,
     pub hint: Option<String>,
     pub options: Vec<String>,
+    /// Correct options of a "select all that apply" question; empty for single answer questions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<String>,
+    /// CEH domains / topics, see `helper::domains`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<Difficulty>,
+    /// Why the correct answer is right, for after the question was answered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    /// Why a wrong option is wrong, keyed by the option text so it survives shuffling.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub distractors: BTreeMap<String, String>,
+    /// Where to read up on it, e.g. "Module 03: Scanning Networks".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+    /// Bank file the question was loaded from; set by the loader, never written back.
+    #[serde(skip)]
+    pub source: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
 }
 impl Question {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
+            id: "".to_string(),
             question: "".to_string(),
             answer: "".to_string(),
             hint: None,
             options: vec!["".to_string(); 4],
+            answers: vec![],
+            tags: vec![],
+            difficulty: None,
+            explanation: None,
+            distractors: BTreeMap::new(),
+            references: vec![],
+            source: None,
+        }
+    }
+
+    pub fn has_explanation(&self) -> bool {
+        self.explanation.is_some() || !self.distractors.is_empty() || !self.references.is_empty()
+    }
+
+    pub fn is_multi_answer(&self) -> bool {
+        !self.answers.is_empty()
+    }
+
+    /// Indexes of the options that are correct answers.
+    pub fn correct_indexes(&self) -> Vec<usize> {
+        let correct = self.correct_answers();
+        self.options.iter().enumerate().filter(|(_, o)| correct.contains(&o.as_str())).map(|(i, _)| i).collect()
+    }
+
+    /// The option texts that make up a correct response.
+    pub fn correct_answers(&self) -> Vec<&str> {
+        if self.is_multi_answer() {
+            self.answers.iter().map(String::as_str).collect()
+        } else {
+            vec![self.answer.as_str()]
         }
     }
 }
 
+/// Content hash (64 bit FNV-1a over question text and options) used to derive new ids.
+///
+/// The hash is only used when a question has no id yet; once stored the id never changes,
+/// so typo fixes in the text do not break history or bookmarks.
+pub fn content_id(question: &Question) -> String {
+    let parts = std::iter::once(question.question.trim()).chain(question.options.iter().map(|o| o.trim()));
+    let hash = fnv1a(parts.flat_map(|part| part.bytes().chain(std::iter::once(0x1f))));
+    format!("{:016x}", hash)
+}
+
+/// 64 bit FNV-1a: stable across runs and platforms, unlike `DefaultHasher`.
+pub fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Gives every question without an id a unique one. Returns how many ids were assigned.
+pub fn assign_missing_ids(questions: &mut [Question]) -> usize {
+    let mut taken: std::collections::HashSet<String> = questions.iter()
+        .filter(|q| !q.id.is_empty())
+        .map(|q| q.id.clone())
+        .collect();
+    let mut assigned = 0;
+    for question in questions.iter_mut().filter(|q| q.id.is_empty()) {
+        let base = content_id(question);
+        let mut id = base.clone();
+        let mut suffix = 2;
+        while taken.contains(&id) {
+            id = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+        taken.insert(id.clone());
+        question.id = id;
+        assigned += 1;
+    }
+    assigned
+}
+
+/// Reads one bank file, marking every question with the bank as its source.
+pub fn load_bank(path: &Path) -> anyhow::Result<Vec<Question>> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Unable to read {}: {}", path.display(), e))?;
+    let mut questions: Vec<Question> = serde_json::from_str(&data).map_err(|e| {
+        let preview = data.chars().take(100).collect::<String>();
+        anyhow::anyhow!("JSON parsing of {} failed: {}\nData preview: {}", path.display(), e, preview)
+    })?;
+    let source = bank::source_name(path);
+    for question in questions.iter_mut() {
+        question.source = Some(source.clone());
+    }
+    Ok(questions)
+}
+
+/// Replaces the question with the same id in whichever configured bank holds it.
+pub fn update_question_in_json(question: Question) -> anyhow::Result<()> {
+    for path in bank::bank_paths() {
+        let updated = bank::modify_bank(&path, |questions| {
+            match questions.iter_mut().find(|q| q.id == question.id) {
+                Some(slot) => {
+                    *slot = question.clone();
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        })?;
+        if updated {
+            println!("Question updated in {}.", path.display());
+            return Ok(());
+        }
+    }
+    anyhow::bail!("no question with id {} in the question banks", question.id)
+}
+
+/// Applies `change` to every configured bank and writes back the ones it changed.
+/// `change` returns how many questions it modified; the total is returned.
+fn rewrite_banks(mut change: impl FnMut(&mut [Question]) -> usize) -> anyhow::Result<usize> {
+    let mut total = 0;
+    for path in bank::bank_paths() {
+        bank::modify_bank(&path, |questions| {
+            let changed = change(questions);
+            total += changed;
+            Ok(changed > 0)
+        })?;
+    }
+    Ok(total)
+}
+
+/// Stores the keyword based tag suggestions on untagged questions of every bank.
+/// Returns the number of questions that got tags.
+pub fn apply_tag_suggestions() -> anyhow::Result<usize> {
+    rewrite_banks(helper::domains::apply_suggestions)
+}
+
+/// Back-fills ids into every bank. Returns the number of questions that got a new id.
+pub fn migrate_question_ids() -> anyhow::Result<usize> {
+    rewrite_banks(assign_missing_ids)
+}
+
 // Enum to handle different types of duplicate checks
 #[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash)]
 pub enum QuestionType {
@@ -52,6 +215,22 @@ pub enum QuestionType {
     DefaultItem,        // Placeholder for default case
 }
 
+/// Result of presenting a single question to the user.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Eq, PartialEq, Hash)]
+pub enum Outcome {
+    Correct,
+    Wrong,
+    Skipped,
+}
+
+/// What the user did with a question: the outcome plus the details we keep in the history.
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq, Hash)]
+pub struct Answer {
+    pub outcome: Outcome,
+    pub chosen: Option<String>,
+    pub hint_used: bool,
+}
+
 #[derive(Debug, Clone,  Deserialize, Serialize, Eq, PartialEq, Hash)]
 pub struct TypedQuestion {
     pub qtype: QuestionType,
@@ -66,37 +245,30 @@ impl TypedQuestion {
     }
 }
 
+/// Loads the pool from the configured question source (see `helper::source`).
 pub fn load_question_pool() -> Vec<Question> {
-    if helper::is_use_local() {
-        let data = match fs::read_to_string("questions.json") {
-            Ok(content) => content,
-            Err(_) => {
-                eprintln!("Error: Unable to read file");
-                return vec![];
-            }
-        };
-
-        match serde_json::from_str::<Vec<Question>>(&data) {
-            Ok(questions) => questions,
-            Err(e) => {
-                eprintln!("Error: JSON parsing failed:");
-                eprintln!("  {}", e);
-                dbg!(e);
-                let preview = data.chars().take(100).collect::<String>();
-                eprintln!("Data preview: {}", preview);
-                vec![]
-            },
-        }
-    } else {
-        // Keep the existing code for the non-local case
+    let source = helper::source::configured_source();
+    let mut questions = source.load().unwrap_or_else(|e| {
+        eprintln!("Error loading questions from {}: {}", source.name(), e);
         vec![]
+    });
+    let assigned = assign_missing_ids(&mut questions);
+    if assigned > 0 {
+        eprintln!("Note: {} questions have no stored id yet, run `db migrate` to persist them.", assigned);
     }
+    questions
 }
-pub fn choose_questions(pool: &Vec<Question>, num_questions: usize) -> Vec<Question> {
+
+pub fn choose_questions(pool: &[Question], num_questions: usize) -> Vec<Question> {
     let mut rng = rand::thread_rng();
     pool.choose_multiple(&mut rng, num_questions).cloned().collect()
 }
 
+/// Same options, answer and hint; used together with equal question text.
+pub fn is_exact_duplicate(question: &Question, other: &Question) -> bool {
+    question.answer == other.answer && question.hint == other.hint && question.options == other.options
+}
+
 pub fn check_for_duplicates(question_pool: &Vec<Question>) -> Vec<TypedQuestion> {
     let mut typed_questions: Vec<TypedQuestion> = Vec::new();
     let mut question_map: HashMap<String, Vec<&Question>> = HashMap::new();
@@ -105,7 +277,7 @@ pub fn check_for_duplicates(question_pool: &Vec<Question>) -> Vec<TypedQuestion>
     for question in question_pool {
         question_map
             .entry(question.question.clone())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(question);
     }
 
@@ -113,11 +285,7 @@ pub fn check_for_duplicates(question_pool: &Vec<Question>) -> Vec<TypedQuestion>
     for questions in question_map.values() {
         if questions.len() > 1 {
             for (i, question) in questions.iter().enumerate() {
-                let is_duplicate_node = questions.iter().skip(i + 1).any(|other| {
-                    question.answer == other.answer &&
-                        question.hint == other.hint &&
-                        question.options == other.options
-                });
+                let is_duplicate_node = questions.iter().skip(i + 1).any(|other| is_exact_duplicate(question, other));
 
                 if is_duplicate_node {
                     typed_questions.push(TypedQuestion {
@@ -142,22 +310,108 @@ pub fn check_for_duplicates(question_pool: &Vec<Question>) -> Vec<TypedQuestion>
     typed_questions
 }
 
-pub fn ask_question(question_number: usize, typed_question: &TypedQuestion) -> bool {
-    let question = &typed_question.question;
-    let ca = &question.answer;
-    let options = &question.options;
-    let hint = question.hint.as_deref();
+/// Parses a selection like "2", "1,3" or "1 3" into zero based option indexes.
+///
+/// Returns `None` if any entry is not an option number in `1..=num_options`, if an option
+/// is repeated, or if several options are given although only one is allowed.
+pub fn parse_selection(input: &str, num_options: usize, multiple: bool) -> Option<Vec<usize>> {
+    let mut selection = vec![];
+    for part in input.split(|c: char| c == ',' || c.is_whitespace()).filter(|p| !p.is_empty()) {
+        let number = part.parse::<usize>().ok()?;
+        if !(1..=num_options).contains(&number) || selection.contains(&(number - 1)) {
+            return None;
+        }
+        selection.push(number - 1);
+    }
+    if selection.is_empty() || (!multiple && selection.len() > 1) {
+        return None;
+    }
+    selection.sort_unstable();
+    Some(selection)
+}
 
+/// Clears the screen and prints a question with its numbered options.
+///
+/// `title` is shown above the question text, `selected` options are marked with `>`.
+pub fn show_question(title: &str, question: &Question, selected: &[usize]) {
     sleep(Duration::from_micros(300));
     print!("{esc}[2J{esc}[1;1H", esc = 27 as char); // Clear screen
-    let _ = banner().expect("Banner failed to print");
-    println!("Question {}\n\n{}", question_number, question.question);
-    for (i, option) in options.iter().enumerate() {
-        println!("{}. {}", i + 1, option.cyan());
+    banner(get_app_config()).expect("Banner failed to print");
+    match &question.source {
+        Some(source) if bank::bank_paths().len() > 1 => println!("{} ({})\n\n{}", title, source.dimmed(), question.question),
+        _ => println!("{}\n\n{}", title, question.question),
     }
+    if question.is_multi_answer() {
+        println!("{}", format!("(Select all that apply - {} answers)", question.answers.len()).italic());
+    }
+    for (i, option) in question.options.iter().enumerate() {
+        let marker = if selected.contains(&i) { ">" } else { " " };
+        println!("{}{}. {}", marker, i + 1, option.cyan());
+    }
+}
+
+/// What is shown after a question was answered: the explanation, why the distractors are
+/// wrong (the chosen ones marked with `>`) and the references. `chosen` are indexes into
+/// `question.options`, which may be in presentation order.
+pub fn explanation_lines(question: &Question, chosen: &[usize]) -> Vec<String> {
+    let mut lines = vec![];
+    if let Some(explanation) = &question.explanation {
+        lines.push(format!("Explanation: {}", explanation));
+    }
+    let distractors: Vec<String> = question
+        .options
+        .iter()
+        .enumerate()
+        .filter_map(|(i, option)| {
+            let why = question.distractors.get(option)?;
+            let marker = if chosen.contains(&i) { ">" } else { " " };
+            Some(format!("{}{}. {}: {}", marker, i + 1, option, why))
+        })
+        .collect();
+    if !distractors.is_empty() {
+        lines.push("Why the other options are wrong:".to_string());
+        lines.extend(distractors);
+    }
+    if !question.references.is_empty() {
+        lines.push(format!("References: {}", question.references.join("; ")));
+    }
+    lines
+}
+
+/// Prints the explanation and waits, so it is not cleared by the next question right away.
+fn show_explanation(question: &Question, chosen: &[usize]) {
+    if !question.has_explanation() {
+        return;
+    }
+    println!();
+    for line in explanation_lines(question, chosen) {
+        println!("{}", line.dimmed());
+    }
+    print!("\nPress Enter to continue...");
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+}
 
+/// Asks one question with its options in presentation order until it is answered or skipped.
+/// Returns `None` when the user exits with X.
+pub fn ask_question(question_number: usize, presentation: &Presentation) -> Option<Answer> {
+    let question = &presentation.question;
+    let ca = question.correct_answers().join("; ");
+    let options = &question.options;
+    let hint = question.hint.as_deref();
+    let multiple = question.is_multi_answer();
+    let mut hint_used = false;
+
+    show_question(&format!("Question {}", question_number), question, &[]);
+
+    let range = format!("1-{}", options.len());
     loop {
-        print!("\nYour answer here [1-4] (X to exit): ");
+        if multiple {
+            print!("\nYour answers here [{}, e.g. 1,3] (H hint, S skip, B bookmark, F flag, X exit): ", range);
+        } else {
+            print!("\nYour answer here [{}] (H hint, S skip, B bookmark, F flag, X exit): ", range);
+        }
         io::stdout().flush().unwrap();
         let mut input = String::new();
         io::stdin().read_line(&mut input).unwrap();
@@ -165,66 +419,99 @@ pub fn ask_question(question_number: usize, typed_question: &TypedQuestion) -> b
 
         if input == "X" {
             println!("Exiting the quiz...");
-            process::exit(0);
+            return None;
         }
-
-        match input.parse::<usize>() {
-            Ok(5) => {
-                if let Some(hint_text) = hint {
-                    println!("Hint: {}", hint_text.yellow());
-                } else {
-                    println!("No hint available for this question.");
-                }
+        if input == "S" {
+            println!("{}", format!("Skipped! The correct answer was: {}", ca).yellow());
+            show_explanation(question, &[]);
+            return Some(Answer { outcome: Outcome::Skipped, chosen: None, hint_used });
+        }
+        if input == "B" {
+            helper::marks::prompt_bookmark(&question.id);
+            continue;
+        }
+        if input == "F" {
+            helper::marks::prompt_flag(&question.id);
+            continue;
+        }
+        if input == "H" {
+            hint_used = true;
+            if let Some(hint_text) = hint {
+                println!("Hint: {}", hint_text.yellow());
+            } else {
+                println!("No hint available for this question.");
             }
-            Ok(cor) if (1..=4).contains(&cor) => {
-                if options[cor - 1] == *ca {
+            continue;
+        }
+
+        match parse_selection(&input, options.len(), multiple) {
+            Some(selection) => {
+                let chosen = Some(presentation.chosen(&selection));
+                let outcome = if presentation.is_correct(&selection) {
                     println!("{}", "Correct!".green());
-                    return true;
+                    Outcome::Correct
                 } else {
                     println!(
                         "{}",
                         format!("Wrong! The correct answer was: {}", ca).red()
                     );
-                    return false;
-                }
+                    Outcome::Wrong
+                };
+                show_explanation(question, &selection);
+                return Some(Answer { outcome, chosen, hint_used });
             }
-            _ => {
-                println!("{}", "Invalid input. Please enter a number from 1 to 4 or 'X' to exit.".red());
+            None => {
+                println!("{}", format!("Invalid input. Please enter {} from {}, 'H' for a hint, 'S' to skip, 'B' to bookmark, 'F' to flag or 'X' to exit.",
+                                       if multiple { "option numbers" } else { "a number" }, range).red());
             }
         }
     }
 }
 
+/// Appends a question to the primary bank (the first `--bank`), never to the merged pool,
+/// so questions of other banks are not copied into it.
 pub fn add_question_to_json(new_question: Question) -> anyhow::Result<()> {
-    // Read existing questions
-    let mut questions = load_question_pool();
-
-    // Add the new question
-    questions.push(new_question);
-
-    // Write the updated questions back to the file
-    let json = serde_json::to_string_pretty(&questions)?;
-    let mut file = fs::File::create("questions.json")?;
-    file.write_all(json.as_bytes())?;
+    // Read the primary bank, add the question and write it back while holding the bank lock
+    bank::modify_bank(&bank::primary_bank(), |questions| {
+        questions.push(new_question);
+        assign_missing_ids(questions);
+        Ok(true)
+    })?;
 
     println!("Question added successfully!");
     Ok(())
 }
-#[cfg(feature = "use_clipboard")]
+
+/// Appends several questions to the primary bank in one write.
+pub fn add_questions_to_json(new_questions: Vec<Question>) -> anyhow::Result<()> {
+    bank::modify_bank(&bank::primary_bank(), |questions| {
+        questions.extend(new_questions);
+        assign_missing_ids(questions);
+        Ok(true)
+    })?;
+    Ok(())
+}
+
 pub fn get_question_from_clipboard() -> anyhow::Result<Question> {
     // Retrieve text content from clipboard
-    let mut ctx: ClipboardContext = ClipboardProvider::new().expect("Error creating clipboard context");
-    let clipboard_content = ctx.get_contents().expect("Error getting clipboard content");
-    Ok(create_question_from_text(&clipboard_content)?)
+    let mut ctx: ClipboardContext = ClipboardProvider::new()
+        .map_err(|e| anyhow::anyhow!("Error creating clipboard context: {}", e))?;
+    let clipboard_content = ctx.get_contents()
+        .map_err(|e| anyhow::anyhow!("Error getting clipboard content: {}", e))?;
+    let parsed = helper::parser::parse_question_text(&clipboard_content)?;
+    for warning in &parsed.warnings {
+        eprintln!("{} {}", "warning".yellow().bold(), warning);
+    }
+    if !parsed.is_confident() {
+        eprintln!("{}", format!("Low parse confidence ({:.0}%), please review the question.", parsed.confidence * 100.0).yellow());
+    }
+    Ok(parsed.question)
 }
 
-#[cfg(not(feature = "use_clipboard"))]
-pub fn get_question_from_clipboard() -> anyhow::Result<Question> {Ok(Question::new())}
-
 
 /// Fills in the details of a Question using the Ollama language model.
 ///
-/// This function is only available when the "use_ki" feature is enabled.
+/// Without the "use_ki" feature this function always returns an error.
 ///
 /// # Feature
 ///
@@ -239,6 +526,7 @@ pub fn get_question_from_clipboard() -> anyhow::Result<Question> {Ok(Question::n
 ///
 /// * `question` - A mutable Question struct that contains at least the question text
 ///                and options. The answer and hint fields will be filled by this function.
+/// * `model` - The Ollama model to ask, e.g. `DEFAULT_OLLAMA_MODEL`.
 ///
 /// # Returns
 ///
@@ -271,7 +559,7 @@ pub fn get_question_from_clipboard() -> anyhow::Result<Question> {Ok(Question::n
 /// #         "Madrid".to_string(),
 /// #     ];
 /// #
-/// #     let filled_question = fill_question_from_ollama(question)?;
+/// #     let filled_question = fill_question_from_ollama(question, "mistral-large")?;
 /// #     println!("Answer: {}", filled_question.answer);
 /// #     println!("Hint: {:?}", filled_question.hint);
 /// #     Ok(())
@@ -281,11 +569,11 @@ pub fn get_question_from_clipboard() -> anyhow::Result<Question> {Ok(Question::n
 /// # fn main() {}
 /// ```
 #[cfg(feature = "use_ki")]
-pub fn fill_question_from_ollama(mut question: Question) -> anyhow::Result<Question>{
+pub fn fill_question_from_ollama(mut question: Question, model: &str) -> anyhow::Result<Question>{
     if helper::debug_default_level() > 10 {
         dbg!(&question);
     }
-    let mut question_text = "answer in correct JSON Format (so no comments) only two fields \"option_number  exact (1-4)\": int Number of the option\n\"hint\": some single line hint\n\n} Question:\n\n".to_string();
+    let mut question_text = format!("answer in correct JSON Format (so no comments) only two fields \"option_number  exact (1-{})\": int Number of the option\n\"hint\": some single line hint\n\n}} Question:\n\n", question.options.len());
     question_text += &question.question;
     question_text += "\nOptions:\n";
     let mut i = 0;
@@ -294,7 +582,7 @@ pub fn fill_question_from_ollama(mut question: Question) -> anyhow::Result<Quest
         question_text += &format!("{}:{}\n", i, option);
     }
     dbg!(&question_text);
-    let test = ollama::ask_ollama_model("mistral-large".to_string(), question_text,
+    let test = ollama::ask_ollama_model(model.to_string(), question_text,
         Some(0.7), Some(1.2), Some(40), Some(0.9), Some(100), Some("3."))?;
 
     dbg!(&test.response);
@@ -323,7 +611,7 @@ pub fn fill_question_from_ollama(mut question: Question) -> anyhow::Result<Quest
     dbg!(&json_response);
     let mut correct_option = json_response.option_number;
     let mut hint = json_response.hint;
-    if correct_option == 0 {
+    if correct_option == 0 || correct_option > question.options.len() {
         correct_option = 1;
         hint = "No ollama hint available".to_string();
     }else{
@@ -336,84 +624,26 @@ pub fn fill_question_from_ollama(mut question: Question) -> anyhow::Result<Quest
     Ok(question)
 }
 
+#[cfg(not(feature = "use_ki"))]
+pub fn fill_question_from_ollama(_question: Question, _model: &str) -> anyhow::Result<Question>{
+    anyhow::bail!("this binary was built without Ollama support, rebuild with --features use_ki")
+}
+
 pub fn get_filled_question(question:&Question) -> anyhow::Result<Question> {
     // get question with same question text
     let questions = load_question_pool();
-    let found_question = questions.iter().find(|q| q.question == question.question);
+    let found_question = find_question(&questions, question);
     if let Some(q) = found_question {
-        return Ok(q.clone());
+        Ok(q.clone())
     }else{
-        return Err(anyhow::Error::msg("Question not found"));
+        Err(anyhow::Error::msg("Question not found"))
     }
 }
 
-/// Creates a `Question` struct from a given text input.
-///
-/// This function parses a string containing a question and its multiple-choice options,
-/// and constructs a `Question` struct from it. The input text is expected to have the
-/// question followed by four options, each starting with a letter (A, B, C, D).
-///
-/// # Parameters
-///
-/// * `text` - A string slice containing the question text and options.
-///
-/// # Returns
-///
-/// * `Result<Question, Box<dyn std::error::Error>>` - A Result containing either:
-///   - `Ok(Question)`: A `Question` struct if parsing is successful.
-///   - `Err(Box<dyn std::error::Error>)`: An error if the input format is invalid.
-///
-/// # Errors
-///
-/// This function will return an error if:
-/// - The input text has fewer than two lines.
-/// - The number of parsed options is not exactly 4.
+/// Creates a `Question` from pasted text, see `helper::parser` for the recognised layouts.
+/// Use `parse_question_text` directly to get the confidence and warnings as well.
 pub fn create_question_from_text(text: &str) -> anyhow::Result<Question> {
-    let parts: Vec<&str> = text.split("\n").collect();
-    if parts.len() < 2 {
-        dbg!(&parts);
-        return Err(anyhow::Error::msg("Invalid question format"));
-    }
-    let mut question_text = parts[0].trim().to_string();
-    let mut start_part_index = 1;
-    // dbg!(&question_text);
-    while start_part_index < parts.len() && parts[start_part_index] != "A" && parts[start_part_index] != "\"A,\"" {
-        dbg!(parts[start_part_index]);
-        question_text += " "; // Add a space between parts
-        question_text += parts[start_part_index].trim();
-        start_part_index += 1;
-    }
-    if start_part_index>4 {
-        dbg!(&parts);
-        dbg!(parts[start_part_index..].join("\n"));
-        // return Err(anyhow::Error::msg("Invalid question format"));
-    }
-    let options_text = parts[start_part_index..].join("\n");
-    // dbg!(&options_text);
-    let mut options = Vec::new();
-    let answer = String::new();
-
-    let mut i = 1;
-    for line in options_text.lines() {
-        i += 1;
-        if i % 2 == 0 {
-            continue;
-        } else {
-            options.push(line.trim().to_string());
-        }
-    }
-    // dbg!(&options.len());
-    if options.len() != 4 {
-        dbg!(&options);
-        return Err(anyhow::Error::msg("Invalid number of options"));
-    }
-
-    Ok(Question {
-        question: question_text,
-        answer,
-        hint: None,
-        options,
-    })
+    helper::parser::parse_question_text(text).map(|parsed| parsed.question)
 }
 pub fn do_clipbboard_actions() -> anyhow::Result<Question> {
     let clipboard_question = match get_clipboard_question() {
@@ -423,33 +653,33 @@ pub fn do_clipbboard_actions() -> anyhow::Result<Question> {
             return Err(e);
         }
     };
-    let found_question = check_question_exists(&clipboard_question);
-    if found_question.is_some() {
+    if let Some(found_question) = check_question_exists(&clipboard_question) {
         println!("Question already exists in the pool.");
-        return Ok(found_question.unwrap());
+        return Ok(found_question);
     }
     let filled_question = do_clipboard_question(clipboard_question.clone())?;
-    if &clipboard_question == &filled_question {
-        println!("Question filled by Ollama:");
-        println!("{:#?}", filled_question);
-    }else {
-        add_question_to_json(filled_question.clone())?;
+    if filled_question.answer.is_empty() {
+        println!("{}", "No answer found in the clipboard text, the question was not saved.".yellow());
+    } else {
+        if clipboard_question != filled_question {
+            println!("Question filled by Ollama.");
+        }
+        helper::source::configured_source().add(filled_question.clone())?;
     }
     Ok(filled_question)
 }
 
-#[cfg(feature = "use_clipboard")]
 fn get_clipboard_question() -> anyhow::Result<Question> {
     questions::get_question_from_clipboard()
 }
-#[cfg(not(feature = "use_clipboard"))]
-fn get_clipboard_question() -> anyhow::Result<Question> {
-    Ok(questions::Question::new())
-}
 #[cfg(feature = "use_ki")]
 fn do_clipboard_question(clip_question:Question) -> anyhow::Result<Question> {
+    // The pasted text already named the answer, nothing to ask
+    if !clip_question.answer.is_empty() {
+        return Ok(clip_question);
+    }
     // If Question is created from the clipboard, we ask Ollama about it
-    match questions::fill_question_from_ollama(clip_question.clone()) {
+    match questions::fill_question_from_ollama(clip_question.clone(), DEFAULT_OLLAMA_MODEL) {
         Ok(filled_question) => Ok(filled_question),
         Err(_) => Ok(clip_question)
     }
@@ -462,5 +692,90 @@ fn do_clipboard_question(clip_question:Question) -> anyhow::Result<Question> {
 
 fn check_question_exists(question: &Question) -> Option<Question> {
     let questions = load_question_pool();
-    questions.into_iter().find(|q| q.question == question.question)
+    find_question(&questions, question).cloned()
+}
+
+/// Looks a question up by id, falling back to the question text for entries without one.
+pub fn find_question<'a>(pool: &'a [Question], question: &Question) -> Option<&'a Question> {
+    let id = if question.id.is_empty() { content_id(question) } else { question.id.clone() };
+    pool.iter()
+        .find(|q| q.id == id)
+        .or_else(|| pool.iter().find(|q| q.question == question.question))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(text: &str, options: &[&str]) -> Question {
+        Question {
+            question: text.to_string(),
+            options: options.iter().map(|o| o.to_string()).collect(),
+            ..Question::new()
+        }
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("6", 7, false), Some(vec![5]));
+        assert_eq!(parse_selection("8", 7, false), None);
+        assert_eq!(parse_selection("1,3", 4, false), None);
+        assert_eq!(parse_selection("3, 1", 4, true), Some(vec![0, 2]));
+        assert_eq!(parse_selection("1 1", 4, true), None);
+        assert_eq!(parse_selection("H", 4, true), None);
+        assert_eq!(parse_selection("", 4, true), None);
+    }
+
+    #[test]
+    fn test_correct_answers_for_single_and_multi_answer() {
+        let single = Question { answer: "B".to_string(), ..question("q", &["A", "B"]) };
+        assert_eq!(single.correct_answers(), vec!["B"]);
+        let multi = Question { answers: vec!["A".to_string(), "C".to_string()], ..question("q", &["A", "B", "C"]) };
+        assert!(multi.is_multi_answer());
+        assert_eq!(multi.correct_answers(), vec!["A", "C"]);
+    }
+
+    #[test]
+    fn test_explanation_lines_follow_presentation_order() {
+        let mut q = Question { answer: "22".to_string(), ..question("Which port does SSH use?", &["22", "23", "21"]) };
+        assert!(explanation_lines(&q, &[]).is_empty());
+        q.explanation = Some("SSH listens on 22/tcp.".to_string());
+        q.distractors.insert("23".to_string(), "Telnet".to_string());
+        q.distractors.insert("21".to_string(), "FTP control".to_string());
+        q.references = vec!["Module 04: Enumeration".to_string()];
+        assert_eq!(
+            explanation_lines(&q, &[2]),
+            vec![
+                "Explanation: SSH listens on 22/tcp.",
+                "Why the other options are wrong:",
+                " 2. 23: Telnet",
+                ">3. 21: FTP control",
+                "References: Module 04: Enumeration",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_content_id_is_stable_and_content_based() {
+        let a = question("What is DNSSEC?", &["A", "B"]);
+        assert_eq!(content_id(&a), content_id(&question(" What is DNSSEC? ", &["A", "B"])));
+        assert_ne!(content_id(&a), content_id(&question("What is DNSSEC?", &["A", "C"])));
+        assert_eq!(content_id(&a).len(), 16);
+    }
+
+    #[test]
+    fn test_assign_missing_ids_keeps_existing_and_dedupes() {
+        let mut pool = vec![
+            question("dup", &["A"]),
+            question("dup", &["A"]),
+            Question { id: "fixed".to_string(), ..question("kept", &["A"]) },
+        ];
+        assert_eq!(assign_missing_ids(&mut pool), 2);
+        assert_eq!(pool[1].id, format!("{}-2", pool[0].id));
+        assert_eq!(pool[2].id, "fixed");
+        assert_eq!(assign_missing_ids(&mut pool), 0);
+
+        let edited = Question { question: "kept, typo fixed".to_string(), ..pool[2].clone() };
+        assert_eq!(find_question(&pool, &edited).unwrap().id, "fixed");
+    }
 }